@@ -0,0 +1,130 @@
+use deadline::Deadline;
+use errors;
+use interpact::CircuitBreaker;
+use poison;
+use std::cmp;
+use std::collections::VecDeque;
+use std::sync;
+use std::time;
+
+/// The shortest timeout an `AdaptiveTimeout` hands out, so that a run of instant calls cannot leave every later
+/// call expired before it starts.
+pub const MIN_TIMEOUT: time::Duration = time::Duration::from_millis(1);
+
+/// The samples in the order they were observed, for eviction, and sorted, for reading a percentile.
+#[derive(Debug, Default)]
+struct Samples {
+    observed: VecDeque<time::Duration>,
+    sorted: Vec<time::Duration>,
+}
+
+/// A per-call timeout derived from observed latencies: the `percentile` of the last `max_samples` calls times
+/// `factor`, clamped to `[min, max]`. Until there are samples, the timeout is `max`.
+#[derive(Debug)]
+pub struct AdaptiveTimeout {
+    percentile: f64,
+    factor: f64,
+    min: time::Duration,
+    max: time::Duration,
+    max_samples: usize,
+    samples: sync::Mutex<Samples>,
+}
+
+impl AdaptiveTimeout {
+    /// Raises `min` to `MIN_TIMEOUT`, and `max` to `min`. A `factor` that is not a positive number becomes 1.
+    pub fn new(percentile: f64, factor: f64, min: time::Duration, max: time::Duration) -> AdaptiveTimeout {
+        let min = cmp::max(min, MIN_TIMEOUT);
+        AdaptiveTimeout {
+            percentile: percentile.clamp(0.0, 1.0),
+            factor: if factor.is_finite() && factor > 0.0 { factor } else { 1.0 },
+            min,
+            max: cmp::max(max, min),
+            max_samples: 1000,
+            samples: sync::Mutex::new(Samples::default()),
+        }
+    }
+
+    /// Like `new`, but rejects bounds it would otherwise adjust.
+    pub fn try_new(
+        percentile: f64,
+        factor: f64,
+        min: time::Duration,
+        max: time::Duration,
+    ) -> Result<AdaptiveTimeout, errors::ConfigError> {
+        let invalid = |field, message: &str| {
+            Err(errors::ConfigError {
+                field,
+                message: message.into(),
+            })
+        };
+        if !(0.0..=1.0).contains(&percentile) {
+            return invalid("percentile", "must be between 0 and 1");
+        }
+        if !(factor.is_finite() && factor > 0.0) {
+            return invalid("factor", "must be a positive number");
+        }
+        if min < MIN_TIMEOUT {
+            return invalid("min", "must be at least one millisecond");
+        }
+        if max < min {
+            return invalid("max", "must not be less than min");
+        }
+        Ok(AdaptiveTimeout::new(percentile, factor, min, max))
+    }
+
+    pub fn with_max_samples(mut self, max_samples: usize) -> AdaptiveTimeout {
+        self.max_samples = max_samples;
+        self
+    }
+
+    pub fn observe(&self, latency: time::Duration) {
+        let mut samples = poison::lock(&self.samples);
+        if samples.observed.len() >= self.max_samples {
+            if let Some(evicted) = samples.observed.pop_front() {
+                if let Ok(index) = samples.sorted.binary_search(&evicted) {
+                    samples.sorted.remove(index);
+                }
+            }
+        }
+        samples.observed.push_back(latency);
+        let index = samples.sorted.partition_point(|&sample| sample < latency);
+        samples.sorted.insert(index, latency);
+    }
+
+    pub fn timeout(&self) -> time::Duration {
+        let samples = poison::lock(&self.samples);
+        let sorted = &samples.sorted;
+        if sorted.is_empty() {
+            return self.max;
+        }
+        let rank = ((sorted.len() as f64 * self.percentile).ceil() as usize).clamp(1, sorted.len());
+        // Scaled in seconds and capped first, so that a large factor cannot overflow a Duration.
+        let secs = (sorted[rank - 1].as_secs_f64() * self.factor).min(self.max.as_secs_f64());
+        time::Duration::from_secs_f64(secs).clamp(self.min, self.max)
+    }
+
+    pub fn deadline(&self) -> Deadline {
+        Deadline::after(self.timeout())
+    }
+}
+
+impl CircuitBreaker {
+    /// Like `execute_with_deadline`, with the deadline taken from `timeout`. Every completed call feeds its
+    /// latency back, so a call that overran is observed at its full duration and loosens the timeout.
+    pub fn execute_with_adaptive_timeout<T, E, F>(
+        &self,
+        timeout: &AdaptiveTimeout,
+        task: F,
+    ) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        F: FnOnce(Deadline) -> Result<T, E>,
+    {
+        let started = self.now();
+        let result = self.execute_with_deadline(self.deadline_after(timeout.timeout()), task);
+        match result {
+            Err(ref err) if err.kind != errors::CircuitBreakerErrorKind::TimeoutError => {}
+            _ => timeout.observe(self.now().saturating_duration_since(started)),
+        }
+        result
+    }
+}
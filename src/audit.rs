@@ -0,0 +1,59 @@
+use interpact::CircuitBreaker;
+use poison;
+use std::collections::VecDeque;
+use std::time;
+
+const MAX_ENTRIES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum AuditAction {
+    ForceOpen,
+    ForceClosed,
+    Reset,
+    ClearCounts,
+    PauseRecording,
+    ResumeRecording,
+    AddProfile(String),
+    SwitchProfile(String),
+    ScheduleProfile(String),
+    AddMaintenanceWindow,
+    ClearMaintenanceWindows,
+}
+
+/// One manual action taken on a breaker, with the operator's reason if one was given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AuditEntry {
+    pub at: time::SystemTime,
+    pub action: AuditAction,
+    pub reason: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct AuditLog {
+    entries: ::std::sync::Mutex<VecDeque<AuditEntry>>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn audit(&self, action: AuditAction, reason: Option<&str>) {
+        let entry = AuditEntry {
+            at: self.wall(),
+            action,
+            reason: reason.map(String::from),
+        };
+        {
+            let mut entries = poison::lock(&self.audit_log.entries);
+            if entries.len() >= MAX_ENTRIES {
+                entries.pop_front();
+            }
+            entries.push_back(entry.clone());
+        }
+        self.listeners.notify_audit(self, &entry);
+    }
+
+    /// The latest manual actions, oldest first. Only the last 256 are kept.
+    pub fn audit_log(&self) -> Vec<AuditEntry> {
+        poison::lock(&self.audit_log.entries).iter().cloned().collect()
+    }
+}
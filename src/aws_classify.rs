@@ -0,0 +1,67 @@
+use outcome::Outcome;
+
+const THROTTLING_CODES: &[&str] = &[
+    "Throttling",
+    "ThrottlingException",
+    "ThrottledException",
+    "RequestThrottledException",
+    "TooManyRequestsException",
+    "ProvisionedThroughputExceededException",
+    "TransactionInProgressException",
+    "RequestLimitExceeded",
+    "BandwidthLimitExceeded",
+    "LimitExceededException",
+    "RequestThrottled",
+    "SlowDown",
+    "PriorRequestNotComplete",
+    "EC2ThrottledException",
+];
+
+const TRANSIENT_CODES: &[&str] = &[
+    "RequestTimeout",
+    "RequestTimeoutException",
+    "InternalError",
+    "ServiceUnavailable",
+];
+
+/// Classifies AWS service errors by HTTP status and error code, using the codes the AWS SDKs retry on.
+/// Throttling counts as `throttling` (a failure by default); 5xx and transient codes are failures; other 4xx
+/// errors describe the request and are ignored. In an aws-sdk-rust interceptor, feed it the response status and
+/// the `code()` of the error metadata.
+#[derive(Debug, Clone, Copy)]
+pub struct AwsClassifier {
+    pub throttling: Outcome,
+}
+
+impl Default for AwsClassifier {
+    fn default() -> AwsClassifier {
+        AwsClassifier {
+            throttling: Outcome::Failure,
+        }
+    }
+}
+
+impl AwsClassifier {
+    pub fn new() -> AwsClassifier {
+        AwsClassifier::default()
+    }
+
+    pub fn is_throttling(code: &str) -> bool {
+        THROTTLING_CODES.contains(&code)
+    }
+
+    /// `status` is `None` for errors that produced no response, which count as failures.
+    pub fn classify_error(&self, status: Option<u16>, code: Option<&str>) -> Outcome {
+        if code.is_some_and(AwsClassifier::is_throttling) || status == Some(429) {
+            return self.throttling;
+        }
+        if code.is_some_and(|code| TRANSIENT_CODES.contains(&code)) {
+            return Outcome::Failure;
+        }
+        match status {
+            Some(status) if (200..300).contains(&status) => Outcome::Success,
+            Some(status) if (400..500).contains(&status) => Outcome::Ignored,
+            _ => Outcome::Failure,
+        }
+    }
+}
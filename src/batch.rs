@@ -0,0 +1,185 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use poison;
+use std::sync::Mutex;
+use std::thread;
+
+#[cfg(feature = "async")]
+use futures::future::{self, Future, FutureExt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchAdmission {
+    PerBatch,
+    PerItem,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BatchSummary {
+    pub admitted: u32,
+    pub rejected: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+}
+
+#[derive(Debug)]
+pub struct Batch<T, E> {
+    pub results: Vec<Result<Result<T, E>, errors::CircuitBreakerError>>,
+    pub summary: BatchSummary,
+}
+
+impl<T, E> Batch<T, E> {
+    fn new(results: Vec<Result<Result<T, E>, errors::CircuitBreakerError>>) -> Batch<T, E> {
+        let mut summary = BatchSummary::default();
+        for result in &results {
+            match *result {
+                Ok(Ok(_)) => summary.succeeded += 1,
+                Ok(Err(_)) => summary.failed += 1,
+                Err(_) => summary.rejected += 1,
+            }
+        }
+        summary.admitted = summary.succeeded + summary.failed;
+        Batch { results, summary }
+    }
+}
+
+fn rejected_all<T, E>(err: &errors::CircuitBreakerError, len: usize) -> Batch<T, E> {
+    Batch::new((0..len).map(|_| Err(err.clone())).collect())
+}
+
+impl CircuitBreaker {
+    /// Runs `tasks` in order. With `PerBatch` admission the whole batch counts as a single call that
+    /// fails if any task failed; with `PerItem` every task is admitted and recorded on its own.
+    pub fn execute_batch<T, E, I, F>(&self, admission: BatchAdmission, tasks: I) -> Batch<T, E>
+    where
+        I: IntoIterator<Item = F>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let tasks: Vec<F> = tasks.into_iter().collect();
+        match admission {
+            BatchAdmission::PerBatch => match Permit::acquire(self) {
+                Ok(permit) => {
+                    let batch = Batch::new(tasks.into_iter().map(|task| Ok(task())).collect());
+                    permit.record(Outcome::from_success(batch.summary.failed == 0));
+                    batch
+                }
+                Err(err) => rejected_all(&err, tasks.len()),
+            },
+            BatchAdmission::PerItem => Batch::new(
+                tasks
+                    .into_iter()
+                    .map(|task| {
+                        let permit = Permit::acquire(self)?;
+                        let result = task();
+                        permit.record(Outcome::from_success(result.is_ok()));
+                        Ok(result)
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Runs `tasks` on up to `threads` threads, each task admitted and recorded on its own. Once a task is
+    /// rejected, the tasks not yet started are rejected with the same error without being tried, so a circuit
+    /// that opens mid-batch stops the batch. Results are in the order of `tasks`.
+    pub fn execute_batch_parallel<T, E, I, F>(&self, threads: usize, tasks: I) -> Batch<T, E>
+    where
+        I: IntoIterator<Item = F>,
+        F: FnOnce() -> Result<T, E> + Send,
+        T: Send,
+        E: Send,
+    {
+        let queue = Mutex::new(tasks.into_iter().enumerate().collect::<Vec<_>>().into_iter());
+        let rejection: Mutex<Option<errors::CircuitBreakerError>> = Mutex::new(None);
+        let mut results: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.max(1))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let next = poison::lock(&queue).next();
+                            let (index, task) = match next {
+                                Some(next) => next,
+                                None => return done,
+                            };
+                            if let Some(ref err) = *poison::lock(&rejection) {
+                                done.push((index, Err(err.clone())));
+                                continue;
+                            }
+                            match Permit::acquire(self) {
+                                Ok(permit) => {
+                                    let result = task();
+                                    permit.record(Outcome::from_success(result.is_ok()));
+                                    done.push((index, Ok(result)));
+                                }
+                                Err(err) => {
+                                    poison::lock(&rejection).get_or_insert_with(|| err.clone());
+                                    done.push((index, Err(err)));
+                                }
+                            }
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| ::std::panic::resume_unwind(panic)))
+                .collect()
+        });
+        results.sort_by_key(|&(index, _)| index);
+        Batch::new(results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    /// Like `execute_batch`, but admitted tasks run concurrently.
+    #[cfg(feature = "async")]
+    pub fn execute_batch_async<'a, T, E, I, F, Fut>(
+        &'a self,
+        admission: BatchAdmission,
+        tasks: I,
+    ) -> impl Future<Output = Batch<T, E>> + 'a
+    where
+        I: IntoIterator<Item = F>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>> + 'a,
+        T: 'a,
+        E: 'a,
+    {
+        let tasks: Vec<F> = tasks.into_iter().collect();
+        // The permits travel with the futures, so a batch dropped before it finishes gives its calls back.
+        let (batch_permit, admitted): (Option<Permit<&CircuitBreaker>>, Vec<_>) = match admission {
+            BatchAdmission::PerBatch => match Permit::acquire(self) {
+                Ok(permit) => (Some(permit), tasks.into_iter().map(|task| Ok((None, task))).collect()),
+                Err(err) => return future::ready(rejected_all(&err, tasks.len())).left_future(),
+            },
+            BatchAdmission::PerItem => (
+                None,
+                tasks
+                    .into_iter()
+                    .map(|task| Permit::acquire(self).map(|permit| (Some(permit), task)))
+                    .collect(),
+            ),
+        };
+        let calls = admitted.into_iter().map(move |admitted| match admitted {
+            Ok((permit, task)) => task()
+                .map(move |result| {
+                    if let Some(permit) = permit {
+                        permit.record(Outcome::from_success(result.is_ok()));
+                    }
+                    Ok(result)
+                })
+                .left_future(),
+            Err(err) => future::ready(Err(err)).right_future(),
+        });
+        future::join_all(calls)
+            .map(move |results| {
+                let batch = Batch::new(results);
+                if let Some(permit) = batch_permit {
+                    permit.record(Outcome::from_success(batch.summary.failed == 0));
+                }
+                batch
+            })
+            .right_future()
+    }
+}
@@ -0,0 +1,54 @@
+use errors;
+use futures::future::{self, Either, Future, FutureExt};
+use futures_timer::Delay;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use std::panic;
+use std::time;
+use tokio::task;
+
+impl CircuitBreaker {
+    /// Runs a synchronous `task` on tokio's blocking pool, for wrapping blocking SDKs inside async services.
+    /// A task still running after `timeout` is recorded as a failure and reported as a timeout error; it keeps
+    /// running on the pool, since blocking tasks cannot be interrupted. A task that panics is recorded as a
+    /// failure and the panic is resumed.
+    pub fn execute_blocking<'a, T, E, F>(
+        &'a self,
+        timeout: time::Duration,
+        task: F,
+    ) -> impl Future<Output = Result<Result<T, E>, errors::CircuitBreakerError>> + 'a
+    where
+        F: FnOnce() -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: Send + 'static,
+    {
+        future::lazy(move |_| self.permit()).then(move |permit| match permit {
+            Ok(permit) => future::select(task::spawn_blocking(task), Delay::new(timeout))
+                .map(move |finished| match finished {
+                    Either::Left((Ok(result), _)) => {
+                        permit.record(Outcome::from_success(result.is_ok()));
+                        Ok(result)
+                    }
+                    Either::Left((Err(err), _)) => {
+                        permit.failure();
+                        if err.is_panic() {
+                            panic::resume_unwind(err.into_panic());
+                        }
+                        Err(errors::CircuitBreakerError {
+                            kind: errors::CircuitBreakerErrorKind::CancelledError,
+                            message: "The blocking task was cancelled".into(),
+                        })
+                    }
+                    Either::Right(_) => {
+                        permit.failure();
+                        Err(errors::CircuitBreakerError {
+                            kind: errors::CircuitBreakerErrorKind::TimeoutError,
+                            message: "The blocking task did not finish in time".into(),
+                        })
+                    }
+                })
+                .left_future(),
+            Err(err) => future::ready(Err(err)).right_future(),
+        })
+    }
+}
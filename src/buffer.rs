@@ -0,0 +1,120 @@
+use outcome::Outcome;
+use poison;
+use shard;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time;
+
+/// Buffers the outcomes of a Closed breaker per thread, and hands them to the breaker once `max_outcomes`
+/// have piled up or the oldest has waited `max_delay`. Trips, and the counts, lag behind by up to that much;
+/// reading the breaker's state or counts hands over whatever is buffered at the time. A buffer whose thread
+/// stops recording is handed over by the next call admitted on any thread once it is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Buffering {
+    pub max_outcomes: usize,
+    pub max_delay: time::Duration,
+}
+
+/// The outcomes one thread has buffered, each with the generation it was admitted in.
+pub(crate) struct Buffered {
+    outcomes: Vec<(u64, Outcome)>,
+    since: Option<time::Instant>,
+}
+
+impl Buffered {
+    /// Buffers an outcome; true once the buffer is due to be handed over.
+    pub(crate) fn push(&mut self, generation: u64, outcome: Outcome, now: time::Instant, buffering: Buffering) -> bool {
+        self.outcomes.push((generation, outcome));
+        let since = *self.since.get_or_insert(now);
+        self.outcomes.len() >= buffering.max_outcomes || now.saturating_duration_since(since) >= buffering.max_delay
+    }
+
+    /// When the oldest buffered outcome will have waited `max_delay`, if there is one.
+    fn due_at(&self, buffering: Buffering) -> Option<time::Instant> {
+        self.since.map(|since| since + buffering.max_delay)
+    }
+
+    /// Takes the buffered outcomes in the order they were recorded, keeping the buffer's capacity.
+    pub(crate) fn drain(&mut self) -> ::std::vec::Drain<'_, (u64, Outcome)> {
+        self.since = None;
+        self.outcomes.drain(..)
+    }
+}
+
+pub(crate) struct Buffers {
+    pub(crate) buffering: Buffering,
+    buffers: Box<[Mutex<Buffered>]>,
+    started: time::Instant,
+    /// Nanoseconds after `started` at which some buffer may be due, or `u64::MAX` if none is known to be.
+    next_due: AtomicU64,
+}
+
+impl Buffers {
+    /// One buffer per available core; threads beyond that share them.
+    pub(crate) fn new(buffering: Buffering, now: time::Instant) -> Buffers {
+        let count = thread::available_parallelism().map_or(8, |count| count.get());
+        Buffers {
+            buffering,
+            buffers: (0..count)
+                .map(|_| {
+                    Mutex::new(Buffered {
+                        outcomes: Vec::with_capacity(buffering.max_outcomes),
+                        since: None,
+                    })
+                })
+                .collect(),
+            started: now,
+            next_due: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    /// Buffers an outcome in the calling thread's buffer; true once that buffer is due to be handed over.
+    pub(crate) fn push(&self, buffered: &mut Buffered, generation: u64, outcome: Outcome, now: time::Instant) -> bool {
+        let first = buffered.since.is_none();
+        let due = buffered.push(generation, outcome, now, self.buffering);
+        if first && !due {
+            self.schedule(now + self.buffering.max_delay);
+        }
+        due
+    }
+
+    /// Takes the buffers that are due to be handed over, leaving the rest scheduled. A buffer another thread
+    /// is busy with is checked again on the next call.
+    pub(crate) fn take_due(&self, now: time::Instant, mut hand_over: impl FnMut(&mut Buffered)) {
+        if self.next_due.load(Ordering::Relaxed) > self.nanos(now) {
+            return;
+        }
+        self.next_due.store(u64::MAX, Ordering::Relaxed);
+        for buffered in self.buffers.iter() {
+            match poison::try_lock(buffered) {
+                Some(mut buffered) => match buffered.due_at(self.buffering) {
+                    Some(due_at) if due_at <= now => hand_over(&mut buffered),
+                    Some(due_at) => self.schedule(due_at),
+                    None => {}
+                },
+                None => self.schedule(now),
+            }
+        }
+    }
+
+    fn schedule(&self, at: time::Instant) {
+        self.next_due.fetch_min(self.nanos(at), Ordering::Relaxed);
+    }
+
+    fn nanos(&self, at: time::Instant) -> u64 {
+        at.saturating_duration_since(self.started)
+            .as_nanos()
+            .min(u128::from(u64::MAX)) as u64
+    }
+
+    /// The calling thread's buffer.
+    pub(crate) fn local(&self) -> &Mutex<Buffered> {
+        &self.buffers[shard::thread_index() % self.buffers.len()]
+    }
+
+    pub(crate) fn all(&self) -> &[Mutex<Buffered>] {
+        &self.buffers
+    }
+}
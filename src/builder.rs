@@ -0,0 +1,138 @@
+use buffer::Buffering;
+use errors;
+use interpact::{CircuitBreaker, Counts, Options, Reason, State, TripPolicy};
+use std::marker::PhantomData;
+use std::time;
+
+/// `Builder` state before a name has been given; `build` is not available yet.
+pub struct Unnamed;
+pub struct Named;
+/// `Builder` state before a trip policy has been chosen; only one of the policy methods can be called.
+pub struct NoPolicy;
+pub struct WithPolicy;
+
+/// A builder for `CircuitBreaker` that only offers `build` once a name is set, and that accepts a single trip policy.
+pub struct Builder<'a, N, P> {
+    options: Options<'a>,
+    marker: PhantomData<(N, P)>,
+}
+
+impl<'a> Default for Builder<'a, Unnamed, NoPolicy> {
+    fn default() -> Builder<'a, Unnamed, NoPolicy> {
+        Builder::new()
+    }
+}
+
+impl<'a> Builder<'a, Unnamed, NoPolicy> {
+    pub fn new() -> Builder<'a, Unnamed, NoPolicy> {
+        Builder {
+            options: Options::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, N, P> Builder<'a, N, P> {
+    fn to<M, Q>(self) -> Builder<'a, M, Q> {
+        Builder {
+            options: self.options,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn max_requests(mut self, max_requests: u32) -> Builder<'a, N, P> {
+        self.options.max_requests = max_requests;
+        self
+    }
+
+    pub fn success_threshold(mut self, success_threshold: u32) -> Builder<'a, N, P> {
+        self.options.success_threshold = Some(success_threshold);
+        self
+    }
+
+    pub fn interval(mut self, interval: time::Duration) -> Builder<'a, N, P> {
+        self.options.interval = interval;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: time::Duration) -> Builder<'a, N, P> {
+        self.options.timeout = timeout;
+        self
+    }
+
+    pub fn on_state_change(mut self, on_state_change: fn(name: String, from: State, to: State)) -> Builder<'a, N, P> {
+        self.options.on_state_change = on_state_change;
+        self
+    }
+
+    pub fn on_transition(
+        mut self,
+        on_transition: fn(name: String, from: State, to: State, reason: Reason),
+    ) -> Builder<'a, N, P> {
+        self.options.on_transition = on_transition;
+        self
+    }
+
+    /// Counts successes in `shards` per-thread stripes instead of under the breaker's lock; see `Options::counter_shards`.
+    pub fn sharded_counters(mut self, shards: usize) -> Builder<'a, N, P> {
+        self.options.counter_shards = shards;
+        self
+    }
+
+    /// Buffers outcomes per thread, handing them to the breaker in batches; see `Buffering`.
+    pub fn buffer_outcomes(mut self, max_outcomes: usize, max_delay: time::Duration) -> Builder<'a, N, P> {
+        self.options.buffering = Some(Buffering { max_outcomes, max_delay });
+        self
+    }
+
+    /// Sets any of the remaining `Options`. The name and the trip policy are left to `name` and the policy
+    /// methods, so that the builder's state stays true: changes `configure` makes to them are undone.
+    pub fn configure<F: FnOnce(&mut Options<'a>)>(mut self, configure: F) -> Builder<'a, N, P> {
+        let (name, trip_policy, ready_to_trip) = (self.options.name, self.options.trip_policy, self.options.ready_to_trip);
+        configure(&mut self.options);
+        self.options.name = name;
+        self.options.trip_policy = trip_policy;
+        self.options.ready_to_trip = ready_to_trip;
+        self
+    }
+}
+
+impl<'a, P> Builder<'a, Unnamed, P> {
+    pub fn name(mut self, name: &'a str) -> Builder<'a, Named, P> {
+        self.options.name = name;
+        self.to()
+    }
+}
+
+impl<'a, N> Builder<'a, N, NoPolicy> {
+    pub fn consecutive_failures(mut self, threshold: u64) -> Builder<'a, N, WithPolicy> {
+        self.options.trip_policy = Some(TripPolicy::ConsecutiveFailures(threshold));
+        self.to()
+    }
+
+    pub fn failure_rate(mut self, rate: f64, min_requests: u64) -> Builder<'a, N, WithPolicy> {
+        self.options.trip_policy = Some(TripPolicy::FailureRate { rate, min_requests });
+        self.to()
+    }
+
+    pub fn ready_to_trip(mut self, ready_to_trip: fn(counts: Counts) -> bool) -> Builder<'a, N, WithPolicy> {
+        self.options.trip_policy = Some(TripPolicy::Custom(ready_to_trip));
+        self.to()
+    }
+}
+
+impl<'a, P> Builder<'a, Named, P> {
+    pub fn build(self) -> CircuitBreaker {
+        CircuitBreaker::new(self.options)
+    }
+
+    pub fn try_build(self) -> Result<CircuitBreaker, errors::ConfigError> {
+        CircuitBreaker::try_new(self.options)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn builder<'a>() -> Builder<'a, Unnamed, NoPolicy> {
+        Builder::new()
+    }
+}
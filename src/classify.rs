@@ -0,0 +1,145 @@
+use context::Context;
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use std::error;
+use std::io;
+
+#[cfg(feature = "async")]
+use futures::future::{self, Future, FutureExt};
+
+/// Decides how the result of a protected call counts towards the circuit.
+pub trait Classifier<T, E> {
+    fn classify(&self, result: &Result<T, E>) -> Outcome;
+
+    /// Classifies with the call's context at hand. Defaults to ignoring the context.
+    fn classify_in(&self, result: &Result<T, E>, _context: &Context) -> Outcome {
+        self.classify(result)
+    }
+}
+
+impl<T, E, F> Classifier<T, E> for F
+where
+    F: Fn(&Result<T, E>) -> Outcome,
+{
+    fn classify(&self, result: &Result<T, E>) -> Outcome {
+        self(result)
+    }
+}
+
+/// Classifies errors with a function and counts every success as such, for clients whose errors mix broken
+/// connections with logical errors, as redis-rs does (`is_connection_dropped`, `is_timeout`, ...).
+#[derive(Debug, Clone, Copy)]
+pub struct ByError<F>(pub F);
+
+impl<T, E, F> Classifier<T, E> for ByError<F>
+where
+    F: Fn(&E) -> Outcome,
+{
+    fn classify(&self, result: &Result<T, E>) -> Outcome {
+        match *result {
+            Ok(_) => Outcome::Success,
+            Err(ref err) => (self.0)(err),
+        }
+    }
+}
+
+/// Counts connection-level `io::Error`s (refused, reset, aborted, not connected, timed out, broken pipe,
+/// unexpected EOF, address not available) as failures. Every other kind, such as `NotFound`, `InvalidInput` or
+/// `PermissionDenied`, is about the request rather than the downstream's health and is ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoErrorClassifier;
+
+fn classify_io(err: &io::Error) -> Outcome {
+    match err.kind() {
+        io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::NotConnected
+        | io::ErrorKind::TimedOut
+        | io::ErrorKind::BrokenPipe
+        | io::ErrorKind::UnexpectedEof
+        | io::ErrorKind::AddrNotAvailable => Outcome::Failure,
+        _ => Outcome::Ignored,
+    }
+}
+
+impl<T> Classifier<T, io::Error> for IoErrorClassifier {
+    fn classify(&self, result: &Result<T, io::Error>) -> Outcome {
+        match *result {
+            Ok(_) => Outcome::Success,
+            Err(ref err) => classify_io(err),
+        }
+    }
+}
+
+/// Looks for an `io::Error` among an error's sources and classifies it like `IoErrorClassifier`; errors without
+/// one are classified as `otherwise`. Database drivers such as mongodb wrap their network errors this way, while
+/// errors about the request itself (duplicate keys, failed validation) carry no `io::Error`.
+#[derive(Debug, Clone, Copy)]
+pub struct IoSourceClassifier {
+    pub otherwise: Outcome,
+}
+
+impl IoSourceClassifier {
+    pub fn new(otherwise: Outcome) -> IoSourceClassifier {
+        IoSourceClassifier { otherwise }
+    }
+}
+
+impl<T, E: error::Error + 'static> Classifier<T, E> for IoSourceClassifier {
+    fn classify(&self, result: &Result<T, E>) -> Outcome {
+        let err = match *result {
+            Ok(_) => return Outcome::Success,
+            Err(ref err) => err,
+        };
+        let mut source: Option<&(dyn error::Error + 'static)> = Some(err);
+        while let Some(err) = source {
+            if let Some(io) = err.downcast_ref::<io::Error>() {
+                return classify_io(io);
+            }
+            source = err.source();
+        }
+        self.otherwise
+    }
+}
+
+impl CircuitBreaker {
+    pub fn execute_with<T, E, C, F>(&self, classifier: &C, task: F) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        C: Classifier<T, E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = Permit::acquire(self)?;
+        let task_result = task();
+        permit.record(classifier.classify(&task_result));
+        Ok(task_result)
+    }
+
+    /// Like `execute_async`, with the result classified by `classifier`, e.g. an HTTP client's response by its
+    /// status. Admission happens on first poll, and a call dropped while in flight counts as cancelled.
+    #[cfg(feature = "async")]
+    pub fn execute_async_with<'a, T, E, C, F, Fut>(
+        &'a self,
+        classifier: &'a C,
+        task: F,
+    ) -> impl Future<Output = Result<Result<T, E>, errors::CircuitBreakerError>> + 'a
+    where
+        C: Classifier<T, E>,
+        F: FnOnce() -> Fut + 'a,
+        Fut: Future<Output = Result<T, E>> + 'a,
+        T: 'a,
+        E: 'a,
+    {
+        future::lazy(move |_| self.permit()).then(move |permit| match permit {
+            Ok(permit) => task()
+                .map(move |result| {
+                    permit.record(classifier.classify(&result));
+                    Ok(result)
+                })
+                .left_future(),
+            Err(err) => future::ready(Err(err)).right_future(),
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use poison;
+use std::sync::Mutex;
+use std::time;
+
+/// The time source behind a breaker's windows, timeouts, latency history, deadlines and maintenance and
+/// profile schedules. Supplying a `ManualClock` makes those independent of the wall clock, for replaying traces
+/// and simulations.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> time::Instant;
+
+    /// The calendar time, for schedules given as `SystemTime`s.
+    fn wall(&self) -> time::SystemTime {
+        time::SystemTime::now()
+    }
+
+    /// Whether the clock moves on its own. A breaker cannot block a thread waiting for a permit on a clock
+    /// that does not.
+    fn advances(&self) -> bool {
+        true
+    }
+}
+
+/// The monotonic system clock, used unless `Options::clock` says otherwise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> time::Instant {
+        time::Instant::now()
+    }
+}
+
+/// A clock that only moves when told to. It starts at the moment it was created.
+#[derive(Debug)]
+pub struct ManualClock {
+    start: time::Instant,
+    wall_start: time::SystemTime,
+    elapsed: Mutex<time::Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock {
+            start: time::Instant::now(),
+            wall_start: time::SystemTime::now(),
+            elapsed: Mutex::new(time::Duration::from_secs(0)),
+        }
+    }
+
+    pub fn advance(&self, by: time::Duration) {
+        *poison::lock(&self.elapsed) += by;
+    }
+
+    /// Moves the clock to `elapsed` after its start. Moving it backwards is ignored.
+    pub fn set(&self, elapsed: time::Duration) {
+        let mut current = poison::lock(&self.elapsed);
+        if elapsed > *current {
+            *current = elapsed;
+        }
+    }
+
+    /// How far the clock has moved since its start.
+    pub fn elapsed(&self) -> time::Duration {
+        *poison::lock(&self.elapsed)
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> ManualClock {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> time::Instant {
+        self.start + self.elapsed()
+    }
+
+    fn wall(&self) -> time::SystemTime {
+        self.wall_start + self.elapsed()
+    }
+
+    fn advances(&self) -> bool {
+        false
+    }
+}
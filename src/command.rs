@@ -0,0 +1,77 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use std::io::{self, Read};
+use std::process;
+use std::thread;
+use std::time;
+
+// How often a command with a timeout is checked for having exited.
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(10);
+
+fn read_all<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn wait_with_timeout(mut child: process::Child, timeout: time::Duration) -> io::Result<Option<process::Output>> {
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+    let deadline = time::Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        let now = time::Instant::now();
+        if now >= deadline {
+            let _ = child.kill();
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(::std::cmp::min(POLL_INTERVAL, deadline - now));
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    Ok(status.map(|status| process::Output { status, stdout, stderr }))
+}
+
+impl CircuitBreaker {
+    /// Runs `command` to completion and captures its output. Failing to spawn and exiting unsuccessfully count
+    /// as failures. With a `timeout`, a command still running then is killed, recorded as a failure and
+    /// reported as a timeout error.
+    pub fn execute_command(
+        &self,
+        command: &mut process::Command,
+        timeout: Option<time::Duration>,
+    ) -> Result<io::Result<process::Output>, errors::CircuitBreakerError> {
+        let permit = Permit::acquire(self)?;
+        command.stdout(process::Stdio::piped()).stderr(process::Stdio::piped());
+        let result = command.spawn().and_then(|child| match timeout {
+            Some(timeout) => wait_with_timeout(child, timeout),
+            None => child.wait_with_output().map(Some),
+        });
+        match result {
+            Ok(Some(output)) => {
+                permit.record(Outcome::from_success(output.status.success()));
+                Ok(Ok(output))
+            }
+            Ok(None) => {
+                permit.failure();
+                Err(errors::CircuitBreakerError {
+                    kind: errors::CircuitBreakerErrorKind::TimeoutError,
+                    message: "The command did not finish in time and was killed".into(),
+                })
+            }
+            Err(err) => {
+                permit.failure();
+                Ok(Err(err))
+            }
+        }
+    }
+}
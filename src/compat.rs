@@ -0,0 +1,137 @@
+//! Translates circuit breaker settings written for resilience4j or Hystrix into a `Config`, for services
+//! moving over from the JVM with their configuration.
+//!
+//! Property names may carry their usual prefixes (`resilience4j.circuitbreaker.instances.payments.`,
+//! `hystrix.command.default.`) and may be written in camelCase or kebab-case. Settings that do not affect the
+//! breaker, such as `registerHealthIndicator` or Hystrix's `execution.*`, are skipped; settings that affect it
+//! but have no counterpart here are reported as errors rather than dropped silently. Settings left out take the
+//! library's own defaults, not this crate's.
+
+use config::Config;
+use errors;
+
+/// Maps resilience4j circuit breaker properties. Only time-based sliding windows can be expressed, so a
+/// `slidingWindowSize` needs `slidingWindowType: TIME_BASED`, count-based being resilience4j's default; the
+/// HalfOpen state closes after `permittedNumberOfCallsInHalfOpenState` successful calls.
+pub fn resilience4j(properties: &[(&str, &str)]) -> Result<Config, errors::ConfigError> {
+    let mut config = Config {
+        failure_rate: Some(0.5),
+        min_requests: Some(100),
+        timeout_ms: Some(60 * 1000),
+        max_requests: Some(10),
+        success_threshold: Some(10),
+        ..Config::default()
+    };
+    let mut window_size: Option<u64> = None;
+    let mut count_based = true;
+    for &(property, value) in properties {
+        let name = normalize(property.rsplit('.').next().unwrap_or(property));
+        match name.as_str() {
+            "failureratethreshold" => config.failure_rate = Some(percent(property, value)?),
+            "minimumnumberofcalls" => config.min_requests = Some(number(property, value)?),
+            "slidingwindowsize" => window_size = Some(number(property, value)?),
+            "slidingwindowtype" => count_based = value.trim().eq_ignore_ascii_case("count_based"),
+            "waitdurationinopenstate" => config.timeout_ms = Some(duration(property, value)?),
+            "permittednumberofcallsinhalfopenstate" => {
+                let permitted = number(property, value)?;
+                config.max_requests = Some(permitted);
+                config.success_threshold = Some(permitted);
+            }
+            "maxwaitdurationinhalfopenstate" => config.max_half_open_duration_ms = Some(duration(property, value)?),
+            "slowcalldurationthreshold" => config.slow_call_duration_ms = Some(duration(property, value)?),
+            "registerhealthindicator"
+            | "eventconsumerbuffersize"
+            | "automatictransitionfromopentohalfopenenabled"
+            | "writablestacktraceenabled"
+            | "recordexceptions"
+            | "ignoreexceptions"
+            | "baseconfig" => {}
+            _ => return Err(unsupported(property)),
+        }
+    }
+    if let Some(size) = window_size {
+        if count_based {
+            return Err(errors::ConfigError {
+                field: "interval",
+                message: "count-based sliding windows are not supported; use a time-based window".into(),
+            });
+        }
+        config.interval_ms = Some(size.saturating_mul(1000));
+    }
+    Ok(config)
+}
+
+/// Maps Hystrix command properties. HalfOpen lets a single trial call through, as in Hystrix.
+pub fn hystrix(properties: &[(&str, &str)]) -> Result<Config, errors::ConfigError> {
+    let mut config = Config {
+        max_requests: Some(1),
+        failure_rate: Some(0.5),
+        min_requests: Some(20),
+        timeout_ms: Some(5000),
+        interval_ms: Some(10 * 1000),
+        ..Config::default()
+    };
+    for &(property, value) in properties {
+        let name = match property.find("circuitBreaker.").or_else(|| property.find("metrics.")) {
+            Some(start) => normalize(&property[start..]),
+            None if property.contains("execution.") || property.contains("fallback.") => continue,
+            None => normalize(property),
+        };
+        match name.as_str() {
+            "circuitbreaker.requestvolumethreshold" => config.min_requests = Some(number(property, value)?),
+            "circuitbreaker.errorthresholdpercentage" => config.failure_rate = Some(percent(property, value)?),
+            "circuitbreaker.sleepwindowinmilliseconds" => config.timeout_ms = Some(number(property, value)?),
+            "metrics.rollingstats.timeinmilliseconds" => config.interval_ms = Some(number(property, value)?),
+            "circuitbreaker.enabled" if value.trim() == "true" => {}
+            "circuitbreaker.forceopen" | "circuitbreaker.forceclosed" if value.trim() == "false" => {}
+            _ if name.starts_with("metrics.") => {}
+            _ => return Err(unsupported(property)),
+        }
+    }
+    Ok(config)
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|&c| c != '-' && c != '_')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn number<T: ::std::str::FromStr>(property: &str, value: &str) -> Result<T, errors::ConfigError> {
+    value.trim().parse().map_err(|_| malformed(property, value))
+}
+
+fn percent(property: &str, value: &str) -> Result<f64, errors::ConfigError> {
+    let value: f64 = number(property, value)?;
+    Ok(value / 100.0)
+}
+
+/// Milliseconds from a plain number of milliseconds or a number suffixed with `ms`, `s`, `m` or `h`.
+fn duration(property: &str, value: &str) -> Result<u64, errors::ConfigError> {
+    let trimmed = value.trim();
+    let split = trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(trimmed.len());
+    let amount: u64 = trimmed[..split].parse().map_err(|_| malformed(property, value))?;
+    let scale = match &trimmed[split..] {
+        "" | "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => return Err(malformed(property, value)),
+    };
+    Ok(amount.saturating_mul(scale))
+}
+
+fn malformed(property: &str, value: &str) -> errors::ConfigError {
+    errors::ConfigError {
+        field: "properties",
+        message: format!("{} has an unusable value {:?}", property, value),
+    }
+}
+
+fn unsupported(property: &str) -> errors::ConfigError {
+    errors::ConfigError {
+        field: "properties",
+        message: format!("{} has no equivalent", property),
+    }
+}
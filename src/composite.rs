@@ -0,0 +1,84 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use std::ops::Deref;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    All,
+    Any,
+}
+
+/// Protects a call that depends on several downstreams. `all` admits a call only if every member circuit
+/// permits it, `any` if at least one does; the outcome is recorded in every member that admitted the call.
+pub struct Composite<B> {
+    mode: Mode,
+    members: Vec<B>,
+}
+
+impl<B: Deref<Target = CircuitBreaker>> Composite<B> {
+    pub fn all<I: IntoIterator<Item = B>>(members: I) -> Composite<B> {
+        Composite {
+            mode: Mode::All,
+            members: members.into_iter().collect(),
+        }
+    }
+
+    pub fn any<I: IntoIterator<Item = B>>(members: I) -> Composite<B> {
+        Composite {
+            mode: Mode::Any,
+            members: members.into_iter().collect(),
+        }
+    }
+
+    pub fn members(&self) -> &[B] {
+        &self.members
+    }
+
+    /// True for `all`, false for `any`.
+    pub fn requires_all(&self) -> bool {
+        self.mode == Mode::All
+    }
+
+    fn before_request(&self) -> Result<Vec<(&CircuitBreaker, u64)>, errors::CircuitBreakerError> {
+        let mut admitted = Vec::with_capacity(self.members.len());
+        let mut rejection = None;
+        for member in &self.members {
+            match member.before_request() {
+                Ok(generation) => admitted.push((&**member, generation)),
+                Err(err) => {
+                    rejection = Some(err);
+                    if self.mode == Mode::All {
+                        break;
+                    }
+                }
+            }
+        }
+        match rejection {
+            Some(err) if self.mode == Mode::All || admitted.is_empty() => {
+                for (member, generation) in admitted {
+                    member.release(generation);
+                }
+                Err(err)
+            }
+            _ => Ok(admitted),
+        }
+    }
+
+    pub fn execute<T, E, F>(&self, task: F) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let admitted: Vec<_> = self
+            .before_request()?
+            .into_iter()
+            .map(|(member, generation)| Permit::admitted(member, generation))
+            .collect();
+        let task_result = task();
+        for permit in admitted {
+            permit.record(Outcome::from_success(task_result.is_ok()));
+        }
+        Ok(task_result)
+    }
+}
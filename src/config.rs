@@ -0,0 +1,102 @@
+use interpact::{CancelledAs, Options, TripPolicy};
+use std::time;
+
+/// The data-only part of `Options`, for loading from configuration files. Unset fields keep what the options
+/// already had; durations are in milliseconds. `failure_rate` takes precedence over `consecutive_failures`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default, deny_unknown_fields))]
+pub struct Config {
+    pub max_requests: Option<u32>,
+    pub success_threshold: Option<u32>,
+    pub interval_ms: Option<u64>,
+    pub timeout_ms: Option<u64>,
+    pub timeout_jitter: Option<f64>,
+    pub min_open_duration_ms: Option<u64>,
+    pub max_half_open_duration_ms: Option<u64>,
+    pub fast_window_ms: Option<u64>,
+    pub slow_call_duration_ms: Option<u64>,
+    pub idle_reset_ms: Option<u64>,
+    pub max_waiting: Option<usize>,
+    pub max_wait_ms: Option<u64>,
+    pub history: Option<usize>,
+    pub cancelled_as: Option<CancelledAs>,
+    pub consecutive_failures: Option<u64>,
+    pub failure_rate: Option<f64>,
+    pub min_requests: Option<u64>,
+}
+
+fn millis(value: Option<u64>, field: &mut time::Duration) {
+    if let Some(ms) = value {
+        *field = time::Duration::from_millis(ms);
+    }
+}
+
+fn set<T>(value: Option<T>, field: &mut T) {
+    if let Some(value) = value {
+        *field = value;
+    }
+}
+
+impl Config {
+    pub fn apply(&self, options: &mut Options) {
+        set(self.max_requests, &mut options.max_requests);
+        if self.success_threshold.is_some() {
+            options.success_threshold = self.success_threshold;
+        }
+        millis(self.interval_ms, &mut options.interval);
+        millis(self.timeout_ms, &mut options.timeout);
+        set(self.timeout_jitter, &mut options.timeout_jitter);
+        millis(self.min_open_duration_ms, &mut options.min_open_duration);
+        millis(self.max_half_open_duration_ms, &mut options.max_half_open_duration);
+        millis(self.fast_window_ms, &mut options.fast_window);
+        millis(self.slow_call_duration_ms, &mut options.slow_call_duration);
+        millis(self.idle_reset_ms, &mut options.idle_reset);
+        set(self.max_waiting, &mut options.max_waiting);
+        millis(self.max_wait_ms, &mut options.max_wait);
+        set(self.history, &mut options.history);
+        set(self.cancelled_as, &mut options.cancelled_as);
+        if let Some(threshold) = self.consecutive_failures {
+            options.trip_policy = Some(TripPolicy::ConsecutiveFailures(threshold));
+        }
+        if let Some(rate) = self.failure_rate {
+            options.trip_policy = Some(TripPolicy::FailureRate {
+                rate,
+                min_requests: self.min_requests.unwrap_or(1),
+            });
+        }
+    }
+
+    /// A JSON Schema (draft 2020-12) for the serialized form of `Config`, for validating configuration in CI
+    /// or driving configuration UIs. It is written by hand; `tests/config.rs` checks it against `Config`.
+    #[cfg(feature = "serde")]
+    pub fn json_schema() -> &'static str {
+        JSON_SCHEMA
+    }
+}
+
+#[cfg(feature = "serde")]
+const JSON_SCHEMA: &str = r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "interpact circuit breaker configuration",
+  "type": "object",
+  "additionalProperties": false,
+  "properties": {
+    "max_requests": { "type": ["integer", "null"], "minimum": 0, "maximum": 4294967295, "description": "0 allows one request" },
+    "success_threshold": { "type": ["integer", "null"], "minimum": 1, "maximum": 4294967295 },
+    "interval_ms": { "type": ["integer", "null"], "minimum": 0 },
+    "timeout_ms": { "type": ["integer", "null"], "minimum": 0, "description": "0 uses the default of 60 seconds" },
+    "timeout_jitter": { "type": ["number", "null"], "minimum": 0, "maximum": 1 },
+    "min_open_duration_ms": { "type": ["integer", "null"], "minimum": 0 },
+    "max_half_open_duration_ms": { "type": ["integer", "null"], "minimum": 0 },
+    "fast_window_ms": { "type": ["integer", "null"], "minimum": 0 },
+    "slow_call_duration_ms": { "type": ["integer", "null"], "minimum": 0 },
+    "idle_reset_ms": { "type": ["integer", "null"], "minimum": 0 },
+    "max_waiting": { "type": ["integer", "null"], "minimum": 0 },
+    "max_wait_ms": { "type": ["integer", "null"], "minimum": 0 },
+    "history": { "type": ["integer", "null"], "minimum": 0 },
+    "cancelled_as": { "enum": ["ignored", "failure", "success", null] },
+    "consecutive_failures": { "type": ["integer", "null"], "minimum": 1 },
+    "failure_rate": { "type": ["number", "null"], "exclusiveMinimum": 0, "maximum": 1 },
+    "min_requests": { "type": ["integer", "null"], "minimum": 0, "default": 1 }
+  }
+}"#;
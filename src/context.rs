@@ -0,0 +1,54 @@
+use classify::Classifier;
+use errors;
+use interpact::CircuitBreaker;
+use permit::Permit;
+use std::collections::BTreeMap;
+
+/// Per-call metadata, such as a request ID or endpoint, handed to classifiers, hooks and listeners.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Context {
+    entries: BTreeMap<String, String>,
+}
+
+impl Context {
+    pub fn new() -> Context {
+        Context::default()
+    }
+
+    pub fn with(mut self, key: &str, value: &str) -> Context {
+        self.entries.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    pub fn entries(&self) -> &BTreeMap<String, String> {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl CircuitBreaker {
+    /// Like `execute_with`, with `context` passed to the classifier and carried into hooks and call events.
+    pub fn execute_with_context<T, E, C, F>(
+        &self,
+        context: &Context,
+        classifier: &C,
+        task: F,
+    ) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        C: Classifier<T, E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = Permit::admitted(self, self.before_request_in(context)?);
+        let task_result = task();
+        permit.record_in(classifier.classify_in(&task_result, context), context);
+        Ok(task_result)
+    }
+}
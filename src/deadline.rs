@@ -0,0 +1,84 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use std::time;
+
+/// An end-to-end deadline handed to protected tasks so nested calls can share one time budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deadline {
+    at: time::Instant,
+}
+
+impl Deadline {
+    pub fn at(at: time::Instant) -> Deadline {
+        Deadline { at }
+    }
+
+    pub fn after(budget: time::Duration) -> Deadline {
+        Deadline::at(time::Instant::now() + budget)
+    }
+
+    pub fn instant(&self) -> time::Instant {
+        self.at
+    }
+
+    pub fn remaining(&self) -> time::Duration {
+        self.remaining_at(time::Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(time::Instant::now())
+    }
+
+    /// How much of the budget is left at `now`, as read from a `Clock`.
+    pub fn remaining_at(&self, now: time::Instant) -> time::Duration {
+        self.at.saturating_duration_since(now)
+    }
+
+    pub fn is_expired_at(&self, now: time::Instant) -> bool {
+        self.at <= now
+    }
+
+    /// The earlier of the two deadlines, for narrowing an inherited deadline with a local budget.
+    pub fn min(self, other: Deadline) -> Deadline {
+        ::std::cmp::min(self, other)
+    }
+}
+
+pub(crate) fn expired() -> errors::CircuitBreakerError {
+    errors::CircuitBreakerError {
+        kind: errors::CircuitBreakerErrorKind::TimeoutError,
+        message: "The deadline of the call has passed".into(),
+    }
+}
+
+impl CircuitBreaker {
+    /// A deadline `budget` from now on this breaker's clock.
+    pub fn deadline_after(&self, budget: time::Duration) -> Deadline {
+        Deadline::at(self.now() + budget)
+    }
+
+    /// Runs `task` with `deadline`. An already expired deadline rejects the call up front; a task that
+    /// returns after the deadline is recorded as a failure and its result is replaced by a timeout error.
+    pub fn execute_with_deadline<T, E, F>(
+        &self,
+        deadline: Deadline,
+        task: F,
+    ) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        F: FnOnce(Deadline) -> Result<T, E>,
+    {
+        if deadline.is_expired_at(self.now()) {
+            return Err(expired());
+        }
+        let permit = Permit::acquire(self)?;
+        let task_result = task(deadline);
+        if deadline.is_expired_at(self.now()) {
+            permit.failure();
+            return Err(expired());
+        }
+        permit.record(Outcome::from_success(task_result.is_ok()));
+        Ok(task_result)
+    }
+}
@@ -0,0 +1,45 @@
+use interpact::CircuitBreaker;
+
+/// Lifetime totals of a breaker. Unlike `Counts`, they are never cleared, so the difference between two
+/// readings is the traffic in between.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Totals {
+    pub requests: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub rejected: u64,
+    pub not_permitted: u64,
+}
+
+impl Totals {
+    fn since(&self, previous: &Totals) -> Totals {
+        Totals {
+            requests: self.requests.saturating_sub(previous.requests),
+            successes: self.successes.saturating_sub(previous.successes),
+            failures: self.failures.saturating_sub(previous.failures),
+            rejected: self.rejected.saturating_sub(previous.rejected),
+            not_permitted: self.not_permitted.saturating_sub(previous.not_permitted),
+        }
+    }
+}
+
+/// Remembers what one consumer, e.g. a metrics exporter, has already seen. Each consumer keeps its own cursor.
+#[derive(Debug, Clone, Default)]
+pub struct DeltaCursor {
+    previous: Totals,
+}
+
+impl CircuitBreaker {
+    pub fn totals(&self) -> Totals {
+        self.lock_inner().totals
+    }
+
+    /// The totals accumulated since the previous call with the same cursor.
+    pub fn snapshot_delta(&self, cursor: &mut DeltaCursor) -> Totals {
+        let totals = self.totals();
+        let delta = totals.since(&cursor.previous);
+        cursor.previous = totals;
+        delta
+    }
+}
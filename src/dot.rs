@@ -0,0 +1,112 @@
+use composite::Composite;
+use interpact::{CircuitBreaker, State};
+use keyed::Keyed;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Deref;
+
+#[derive(Debug, Clone, Copy)]
+enum Node {
+    Breaker(State),
+    Group,
+    Composite,
+}
+
+/// Renders breakers and how they relate as a Graphviz DOT graph, for a quick picture during incidents.
+/// Breakers are colored by state; edges lead from a breaker to its parent and to its group, and from a
+/// composite to its members. Nodes are identified by name.
+///
+/// ```ignore
+/// let mut dot = Dot::new();
+/// dot.keyed(&breakers).composite("checkout", &checkout);
+/// fs::write("breakers.dot", dot.to_string())?;
+/// ```
+#[derive(Debug, Default)]
+pub struct Dot {
+    nodes: BTreeMap<String, Node>,
+    edges: BTreeSet<(String, String, &'static str)>,
+}
+
+fn escape(id: &str) -> String {
+    id.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct Quoted<'a>(&'a str);
+
+impl<'a> fmt::Display for Quoted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\"", escape(self.0))
+    }
+}
+
+impl Dot {
+    pub fn new() -> Dot {
+        Dot::default()
+    }
+
+    /// Adds `cb`, its chain of parents and its group.
+    pub fn breaker(&mut self, cb: &CircuitBreaker) -> &mut Dot {
+        self.nodes.insert(cb.name().into(), Node::Breaker(cb.state()));
+        if let Some(group) = cb.group() {
+            let group_id = format!("group:{}", group.name());
+            self.nodes.insert(group_id.clone(), Node::Group);
+            self.edges.insert((cb.name().into(), group_id, "group"));
+        }
+        if let Some(parent) = cb.parent() {
+            self.edges.insert((cb.name().into(), parent.name().into(), "parent"));
+            self.breaker(parent);
+        }
+        self
+    }
+
+    pub fn keyed<K: Hash + Eq + Clone>(&mut self, breakers: &Keyed<K>) -> &mut Dot {
+        for (_, cb) in breakers.iter() {
+            self.breaker(&cb);
+        }
+        self
+    }
+
+    /// Adds a node called `name` for `composite`, with an edge to each member.
+    pub fn composite<B: Deref<Target = CircuitBreaker>>(&mut self, name: &str, composite: &Composite<B>) -> &mut Dot {
+        let label = if composite.requires_all() { "all" } else { "any" };
+        self.nodes.insert(name.into(), Node::Composite);
+        for member in composite.members() {
+            self.edges.insert((name.into(), member.name().into(), label));
+            self.breaker(member);
+        }
+        self
+    }
+}
+
+impl fmt::Display for Dot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "digraph circuit_breakers {{")?;
+        writeln!(f, "    node [style=filled];")?;
+        for (name, node) in &self.nodes {
+            match *node {
+                Node::Breaker(state) => {
+                    let color = match state {
+                        State::Closed => "palegreen",
+                        State::HalfOpen => "gold",
+                        State::Open => "tomato",
+                    };
+                    writeln!(
+                        f,
+                        "    {} [label=\"{}\\n{}\", fillcolor={}];",
+                        Quoted(name),
+                        escape(name),
+                        state,
+                        color
+                    )?;
+                }
+                Node::Group => writeln!(f, "    {} [shape=box, style=dashed];", Quoted(name))?,
+                Node::Composite => writeln!(f, "    {} [shape=diamond, fillcolor=lightgrey];", Quoted(name))?,
+            }
+        }
+        for (from, to, label) in &self.edges {
+            writeln!(f, "    {} -> {} [label={}];", Quoted(from), Quoted(to), label)?;
+        }
+        writeln!(f, "}}")
+    }
+}
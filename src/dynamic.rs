@@ -0,0 +1,99 @@
+use errors;
+use gobreaker;
+use interpact::{CircuitBreaker, Counts, State};
+use outcome::Outcome;
+use std::time;
+
+/// A call admitted through `DynBreaker::admit`. Unlike a `Permit` it does nothing when dropped, so it must be
+/// handed back to `record`, with `Outcome::Ignored` if the call never ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use = "an admitted call must be recorded"]
+pub struct Admission {
+    generation: u64,
+    started: time::Instant,
+}
+
+/// An object-safe view of a breaker, for plugins and middleware that cannot be generic and hold breakers of
+/// different kinds as `Arc<dyn DynBreaker>`.
+pub trait DynBreaker: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn admit(&self) -> Result<Admission, errors::CircuitBreakerError>;
+
+    /// Records how an admitted call ended; its duration is measured from `admit`.
+    fn record(&self, admission: Admission, outcome: Outcome);
+
+    fn state(&self) -> State;
+
+    fn counts(&self) -> Counts;
+}
+
+impl DynBreaker for CircuitBreaker {
+    fn name(&self) -> &str {
+        CircuitBreaker::name(self)
+    }
+
+    fn admit(&self) -> Result<Admission, errors::CircuitBreakerError> {
+        let generation = self.before_request()?;
+        Ok(Admission {
+            generation,
+            started: self.now(),
+        })
+    }
+
+    fn record(&self, admission: Admission, outcome: Outcome) {
+        self.after_call(admission.generation, outcome, admission.started);
+    }
+
+    fn state(&self) -> State {
+        CircuitBreaker::state(self)
+    }
+
+    fn counts(&self) -> Counts {
+        CircuitBreaker::counts(self)
+    }
+}
+
+impl DynBreaker for gobreaker::CircuitBreaker {
+    fn name(&self) -> &str {
+        self.inner().name()
+    }
+
+    fn admit(&self) -> Result<Admission, errors::CircuitBreakerError> {
+        DynBreaker::admit(self.inner())
+    }
+
+    fn record(&self, admission: Admission, outcome: Outcome) {
+        DynBreaker::record(self.inner(), admission, outcome)
+    }
+
+    fn state(&self) -> State {
+        self.inner().state()
+    }
+
+    fn counts(&self) -> Counts {
+        self.inner().counts()
+    }
+}
+
+impl DynBreaker for gobreaker::TwoStepCircuitBreaker {
+    fn name(&self) -> &str {
+        self.inner().name()
+    }
+
+    fn admit(&self) -> Result<Admission, errors::CircuitBreakerError> {
+        DynBreaker::admit(self.inner())
+    }
+
+    fn record(&self, admission: Admission, outcome: Outcome) {
+        DynBreaker::record(self.inner(), admission, outcome)
+    }
+
+    fn state(&self) -> State {
+        self.inner().state()
+    }
+
+    fn counts(&self) -> Counts {
+        self.inner().counts()
+    }
+}
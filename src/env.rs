@@ -0,0 +1,161 @@
+//! Options from environment variables, for deployments that cannot easily ship a config file.
+//!
+//! For a breaker named `payments` and the prefix `INTERPACT`, each setting is read from
+//! `INTERPACT_PAYMENTS_<SETTING>`, falling back to `INTERPACT_DEFAULT_<SETTING>`. Names are upper-cased and
+//! every character other than a letter or digit becomes `_`, so `payments-eu.v2` reads `INTERPACT_PAYMENTS_EU_V2_*`.
+//!
+//! | Setting | Option |
+//! | --- | --- |
+//! | `MAX_REQUESTS` | `max_requests` |
+//! | `SUCCESS_THRESHOLD` | `success_threshold` |
+//! | `INTERVAL_MS` | `interval` |
+//! | `TIMEOUT_MS` | `timeout` |
+//! | `TIMEOUT_JITTER` | `timeout_jitter` |
+//! | `MIN_OPEN_DURATION_MS` | `min_open_duration` |
+//! | `MAX_HALF_OPEN_DURATION_MS` | `max_half_open_duration` |
+//! | `FAST_WINDOW_MS` | `fast_window` |
+//! | `SLOW_CALL_DURATION_MS` | `slow_call_duration` |
+//! | `IDLE_RESET_MS` | `idle_reset` |
+//! | `MAX_WAITING` | `max_waiting` |
+//! | `MAX_WAIT_MS` | `max_wait` |
+//! | `HISTORY` | `history` |
+//! | `CANCELLED_AS` | `cancelled_as`: `ignored`, `failure` or `success` |
+//! | `CONSECUTIVE_FAILURES` | `trip_policy`: `TripPolicy::ConsecutiveFailures` |
+//! | `FAILURE_RATE`, `MIN_REQUESTS` | `trip_policy`: `TripPolicy::FailureRate` |
+
+use errors;
+use interpact::{CancelledAs, Options, TripPolicy};
+use std::env;
+use std::str::FromStr;
+use std::time;
+
+/// Default options overlaid with the environment for the breaker `name`, then validated.
+pub fn options<'a>(prefix: &str, name: &'a str) -> Result<Options<'a>, errors::ConfigError> {
+    let mut options = Options {
+        name,
+        ..Options::default()
+    };
+    apply(prefix, name, &mut options)?;
+    options.validate()?;
+    Ok(options)
+}
+
+/// Overlays `options` with whichever settings for the breaker `name` are present in the environment.
+pub fn apply(prefix: &str, name: &str, options: &mut Options) -> Result<(), errors::ConfigError> {
+    apply_with(prefix, name, options, |var| env::var(var).ok())
+}
+
+/// Like `apply`, but looks variables up with `lookup` rather than in the process environment.
+pub fn apply_with<F>(prefix: &str, name: &str, options: &mut Options, lookup: F) -> Result<(), errors::ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let vars = Vars {
+        named: format!("{}_{}_", normalize(prefix), normalize(name)),
+        default: format!("{}_DEFAULT_", normalize(prefix)),
+        lookup,
+    };
+    if let Some(max_requests) = vars.parse("MAX_REQUESTS", "max_requests")? {
+        options.max_requests = max_requests;
+    }
+    if let Some(success_threshold) = vars.parse("SUCCESS_THRESHOLD", "success_threshold")? {
+        options.success_threshold = Some(success_threshold);
+    }
+    if let Some(interval) = vars.millis("INTERVAL_MS", "interval")? {
+        options.interval = interval;
+    }
+    if let Some(timeout) = vars.millis("TIMEOUT_MS", "timeout")? {
+        options.timeout = timeout;
+    }
+    if let Some(timeout_jitter) = vars.parse("TIMEOUT_JITTER", "timeout_jitter")? {
+        options.timeout_jitter = timeout_jitter;
+    }
+    if let Some(min_open_duration) = vars.millis("MIN_OPEN_DURATION_MS", "min_open_duration")? {
+        options.min_open_duration = min_open_duration;
+    }
+    if let Some(max_half_open_duration) = vars.millis("MAX_HALF_OPEN_DURATION_MS", "max_half_open_duration")? {
+        options.max_half_open_duration = max_half_open_duration;
+    }
+    if let Some(fast_window) = vars.millis("FAST_WINDOW_MS", "fast_window")? {
+        options.fast_window = fast_window;
+    }
+    if let Some(slow_call_duration) = vars.millis("SLOW_CALL_DURATION_MS", "slow_call_duration")? {
+        options.slow_call_duration = slow_call_duration;
+    }
+    if let Some(idle_reset) = vars.millis("IDLE_RESET_MS", "idle_reset")? {
+        options.idle_reset = idle_reset;
+    }
+    if let Some(max_waiting) = vars.parse("MAX_WAITING", "max_waiting")? {
+        options.max_waiting = max_waiting;
+    }
+    if let Some(max_wait) = vars.millis("MAX_WAIT_MS", "max_wait")? {
+        options.max_wait = max_wait;
+    }
+    if let Some(history) = vars.parse("HISTORY", "history")? {
+        options.history = history;
+    }
+    if let Some((var, value)) = vars.get("CANCELLED_AS") {
+        options.cancelled_as = match value.trim().to_ascii_lowercase().as_str() {
+            "ignored" => CancelledAs::Ignored,
+            "failure" => CancelledAs::Failure,
+            "success" => CancelledAs::Success,
+            _ => return Err(malformed("cancelled_as", &var, &value)),
+        };
+    }
+    if let Some(threshold) = vars.parse("CONSECUTIVE_FAILURES", "trip_policy")? {
+        options.trip_policy = Some(TripPolicy::ConsecutiveFailures(threshold));
+    }
+    if let Some(rate) = vars.parse("FAILURE_RATE", "trip_policy")? {
+        let min_requests = vars.parse("MIN_REQUESTS", "trip_policy")?.unwrap_or(1);
+        options.trip_policy = Some(TripPolicy::FailureRate { rate, min_requests });
+    }
+    Ok(())
+}
+
+struct Vars<F> {
+    named: String,
+    default: String,
+    lookup: F,
+}
+
+impl<F: Fn(&str) -> Option<String>> Vars<F> {
+    fn get(&self, setting: &str) -> Option<(String, String)> {
+        [&self.named, &self.default].iter().find_map(|prefix| {
+            let var = format!("{}{}", prefix, setting);
+            (self.lookup)(&var).map(|value| (var, value))
+        })
+    }
+
+    fn parse<T: FromStr>(&self, setting: &str, field: &'static str) -> Result<Option<T>, errors::ConfigError> {
+        match self.get(setting) {
+            Some((var, value)) => match value.trim().parse() {
+                Ok(parsed) => Ok(Some(parsed)),
+                Err(_) => Err(malformed(field, &var, &value)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn millis(&self, setting: &str, field: &'static str) -> Result<Option<time::Duration>, errors::ConfigError> {
+        Ok(self.parse(setting, field)?.map(time::Duration::from_millis))
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn malformed(field: &'static str, var: &str, value: &str) -> errors::ConfigError {
+    errors::ConfigError {
+        field,
+        message: format!("{} has an unusable value {:?}", var, value),
+    }
+}
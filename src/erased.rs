@@ -0,0 +1,58 @@
+//! Every `execute` variant is generic over the task's error type, so classifying and recording a call never
+//! boxes it. `ErasedBreaker` is the opt-in exception, for storing breakers that guard clients with different
+//! error types side by side, e.g. in a registry of plugins: its errors are boxed, and rejections arrive as a
+//! boxed `CircuitBreakerError`.
+
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use std::error;
+use std::sync::Arc;
+
+pub type BoxError = Box<dyn error::Error + Send + Sync>;
+
+type Classify = Arc<dyn Fn(&(dyn error::Error + 'static)) -> Outcome + Send + Sync>;
+
+/// A breaker that classifies errors by inspecting them as `dyn Error`, such as by downcasting, rather than
+/// through a `Classifier` for one error type.
+#[derive(Clone)]
+pub struct ErasedBreaker {
+    cb: Arc<CircuitBreaker>,
+    classify: Classify,
+}
+
+impl ErasedBreaker {
+    /// Counts every error as a failure.
+    pub fn new(cb: Arc<CircuitBreaker>) -> ErasedBreaker {
+        ErasedBreaker {
+            cb,
+            classify: Arc::new(|_: &(dyn error::Error + 'static)| Outcome::Failure),
+        }
+    }
+
+    pub fn classify_errors<F>(mut self, classify: F) -> ErasedBreaker
+    where
+        F: Fn(&(dyn error::Error + 'static)) -> Outcome + Send + Sync + 'static,
+    {
+        self.classify = Arc::new(classify);
+        self
+    }
+
+    pub fn breaker(&self) -> &Arc<CircuitBreaker> {
+        &self.cb
+    }
+
+    pub fn execute<T, E, F>(&self, task: F) -> Result<T, BoxError>
+    where
+        E: error::Error + Send + Sync + 'static,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let classifier = |result: &Result<T, E>| match *result {
+            Ok(_) => Outcome::Success,
+            Err(ref err) => (self.classify)(err),
+        };
+        match self.cb.execute_with(&classifier, task) {
+            Ok(result) => result.map_err(|err| Box::new(err) as BoxError),
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+}
@@ -1,16 +1,27 @@
-use std::fmt;
+use std::borrow::Cow;
 use std::error;
+use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CircuitBreakerErrorKind {
     StateOpenError,
     TooManyRequestsError,
+    CancelledError,
+    TimeoutError,
+    ShedError,
+    TenantQuotaError,
+    EjectedError,
+    MaintenanceError,
+    CapacityError,
+    OverloadedError,
 }
 
-#[derive(Debug)]
+/// The message is borrowed from a static string unless it names something, such as a tenant, so that
+/// turning a call away never allocates.
+#[derive(Debug, Clone)]
 pub struct CircuitBreakerError {
     pub kind: CircuitBreakerErrorKind,
-    pub message: String,
+    pub message: Cow<'static, str>,
 }
 
 impl error::Error for CircuitBreakerError {
@@ -24,3 +35,36 @@ impl fmt::Display for CircuitBreakerError {
         write!(f, "{}", self.message)
     }
 }
+
+/// An invalid combination of `Options`, reported by `Options::validate` and `CircuitBreaker::try_new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl error::Error for ConfigError {
+    fn description(&self) -> &str {
+        self.message.as_ref()
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Returned when parsing a `State` from a string that names none of the states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStateError {
+    pub input: String,
+}
+
+impl error::Error for ParseStateError {}
+
+impl fmt::Display for ParseStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown circuit breaker state {:?}", self.input)
+    }
+}
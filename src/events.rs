@@ -0,0 +1,185 @@
+use interpact::Transition;
+use listener::Listener;
+use poison;
+use std::collections::VecDeque;
+use std::panic;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+#[cfg(feature = "async")]
+use futures::stream::Stream;
+#[cfg(feature = "async")]
+use futures::task::{Context, Poll, Waker};
+#[cfg(feature = "async")]
+use std::pin::Pin;
+
+/// What a `Dispatched` listener does with a transition that arrives while its queue is full. Listeners run
+/// under the breaker's lock, so there is no option to wait for room: it would stall every caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    DropOldest,
+    DropNewest,
+}
+
+struct Queued {
+    transitions: VecDeque<Transition>,
+    senders: usize,
+    #[cfg(feature = "async")]
+    waker: Option<Waker>,
+}
+
+struct Queue {
+    capacity: usize,
+    overflow: Overflow,
+    dropped: AtomicU64,
+    queued: Mutex<Queued>,
+    not_empty: Condvar,
+}
+
+impl Queue {
+    fn wake(&self, _queued: &mut Queued) {
+        self.not_empty.notify_one();
+        #[cfg(feature = "async")]
+        {
+            if let Some(waker) = _queued.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// A listener that hands transitions over a bounded queue instead of handling them in place, so a slow sink
+/// never delays the call that caused the transition. Clones share the queue and the drop count.
+pub struct Dispatched {
+    queue: Arc<Queue>,
+}
+
+/// The receiving end of a `Dispatched` queue. It ends once every clone of the `Dispatched` is dropped and the
+/// queue is drained; with the `async` feature it is also a `Stream`.
+pub struct Events {
+    queue: Arc<Queue>,
+}
+
+impl Dispatched {
+    pub fn new(capacity: usize, overflow: Overflow) -> (Dispatched, Events) {
+        let queue = Arc::new(Queue {
+            capacity: ::std::cmp::max(capacity, 1),
+            overflow,
+            dropped: AtomicU64::new(0),
+            queued: Mutex::new(Queued {
+                transitions: VecDeque::new(),
+                senders: 1,
+                #[cfg(feature = "async")]
+                waker: None,
+            }),
+            not_empty: Condvar::new(),
+        });
+        (Dispatched { queue: queue.clone() }, Events { queue })
+    }
+
+    /// Runs `listener` on a dedicated thread, which exits once every clone of the returned value is dropped.
+    pub fn spawn<L: Listener + 'static>(capacity: usize, overflow: Overflow, listener: L) -> Dispatched {
+        let (dispatched, events) = Dispatched::new(capacity, overflow);
+        thread::Builder::new()
+            .name("interpact-listener".into())
+            .spawn(move || {
+                for transition in events {
+                    // A panicking listener loses this transition but keeps receiving later ones.
+                    let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| listener.on_transition(&transition)));
+                }
+            })
+            .expect("failed to spawn the listener thread");
+        dispatched
+    }
+
+    /// How many transitions were dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Clone for Dispatched {
+    fn clone(&self) -> Dispatched {
+        poison::lock(&self.queue.queued).senders += 1;
+        Dispatched {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl Drop for Dispatched {
+    fn drop(&mut self) {
+        let mut queued = poison::lock(&self.queue.queued);
+        queued.senders -= 1;
+        if queued.senders == 0 {
+            self.queue.wake(&mut queued);
+        }
+    }
+}
+
+impl Listener for Dispatched {
+    fn on_transition(&self, transition: &Transition) {
+        let queue = &*self.queue;
+        let mut queued = poison::lock(&queue.queued);
+        if queued.transitions.len() >= queue.capacity {
+            match queue.overflow {
+                Overflow::DropOldest => {
+                    queued.transitions.pop_front();
+                    queue.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                Overflow::DropNewest => {
+                    queue.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+        queued.transitions.push_back(transition.clone());
+        queue.wake(&mut queued);
+    }
+}
+
+impl Events {
+    /// Blocks until a transition arrives, or returns `None` once all senders are gone and the queue is empty.
+    pub fn recv(&self) -> Option<Transition> {
+        let mut queued = poison::lock(&self.queue.queued);
+        loop {
+            if let Some(transition) = queued.transitions.pop_front() {
+                return Some(transition);
+            }
+            if queued.senders == 0 {
+                return None;
+            }
+            queued = poison::wait(&self.queue.not_empty, queued);
+        }
+    }
+
+    pub fn try_recv(&self) -> Option<Transition> {
+        poison::lock(&self.queue.queued).transitions.pop_front()
+    }
+}
+
+impl Iterator for Events {
+    type Item = Transition;
+
+    fn next(&mut self) -> Option<Transition> {
+        self.recv()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Stream for Events {
+    type Item = Transition;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Transition>> {
+        let mut queued = poison::lock(&self.queue.queued);
+        if let Some(transition) = queued.transitions.pop_front() {
+            return Poll::Ready(Some(transition));
+        }
+        if queued.senders == 0 {
+            return Poll::Ready(None);
+        }
+        queued.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
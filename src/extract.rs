@@ -0,0 +1,116 @@
+use errors;
+use interpact::CircuitBreaker;
+use keyed::Keyed;
+use outcome::Outcome;
+use permit::Permit;
+use std::hash::Hash;
+use std::sync;
+
+#[cfg(feature = "http")]
+use http;
+
+/// Picks the key of the breaker a request should go through, so middleware can route every request to its
+/// per-key breaker in a `Keyed` set. Closures taking the request implement it too.
+pub trait KeyExtractor<Req> {
+    type Key;
+
+    fn extract(&self, request: &Req) -> Self::Key;
+}
+
+impl<Req, K, F> KeyExtractor<Req> for F
+where
+    F: Fn(&Req) -> K,
+{
+    type Key = K;
+
+    fn extract(&self, request: &Req) -> K {
+        self(request)
+    }
+}
+
+/// Keys HTTP requests by the host of their URI, falling back to the `Host` header, then to the empty string.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByHost;
+
+#[cfg(feature = "http")]
+impl<B> KeyExtractor<http::Request<B>> for ByHost {
+    type Key = String;
+
+    fn extract(&self, request: &http::Request<B>) -> String {
+        if let Some(host) = request.uri().host() {
+            return host.to_string();
+        }
+        request
+            .headers()
+            .get(http::header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .unwrap_or("")
+            .to_string()
+    }
+}
+
+/// Keys HTTP requests by their URI path. For gRPC this is the method, as in `/package.Service/Method`.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByPath;
+
+#[cfg(feature = "http")]
+impl<B> KeyExtractor<http::Request<B>> for ByPath {
+    type Key = String;
+
+    fn extract(&self, request: &http::Request<B>) -> String {
+        request.uri().path().to_string()
+    }
+}
+
+/// Keys HTTP requests by the first `n` segments of their URI path, such as the index in `/logs-2024/_search`.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Copy)]
+pub struct ByPathSegments(pub usize);
+
+#[cfg(feature = "http")]
+impl<B> KeyExtractor<http::Request<B>> for ByPathSegments {
+    type Key = String;
+
+    fn extract(&self, request: &http::Request<B>) -> String {
+        path_prefix(request.uri().path(), self.0)
+    }
+}
+
+/// The first `segments` segments of `path`, without slashes around them: `path_prefix("/logs/_search", 1)` is
+/// `"logs"`. For clients that take paths rather than requests.
+pub fn path_prefix(path: &str, segments: usize) -> String {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .take(segments)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl<K: Hash + Eq + Clone> Keyed<K> {
+    pub fn breaker_for<Req, X>(&self, extractor: &X, request: &Req) -> sync::Arc<CircuitBreaker>
+    where
+        X: KeyExtractor<Req, Key = K>,
+    {
+        self.get(&extractor.extract(request))
+    }
+
+    /// Runs `task` with `request` through the breaker its extracted key maps to.
+    pub fn execute_for<Req, X, T, E, F>(
+        &self,
+        extractor: &X,
+        request: Req,
+        task: F,
+    ) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        X: KeyExtractor<Req, Key = K>,
+        F: FnOnce(Req) -> Result<T, E>,
+    {
+        let cb = self.breaker_for(extractor, &request);
+        let permit = Permit::acquire(cb)?;
+        let task_result = task(request);
+        permit.record(Outcome::from_success(task_result.is_ok()));
+        Ok(task_result)
+    }
+}
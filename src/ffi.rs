@@ -0,0 +1,171 @@
+//! A C API, so that non-Rust code in the same process can share breakers with Rust code. Breakers are passed as
+//! opaque `InterpactBreaker` pointers; one created from Rust with `InterpactBreaker::into_raw` shares its circuit
+//! with the `Arc` it came from. Every pointer must be released exactly once with `interpact_breaker_free`.
+//!
+//! Calls are admitted with `interpact_breaker_admit`, which applies the same limits as `execute`, and each
+//! admission is handed back to exactly one of `interpact_breaker_record_success`,
+//! `interpact_breaker_record_failure` or `interpact_breaker_release`.
+
+use dynamic::{Admission, DynBreaker};
+use interpact::{CircuitBreaker, Options, State, TripPolicy};
+use outcome::Outcome;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+use std::sync::Arc;
+use std::time;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterpactState {
+    Closed = 0,
+    HalfOpen = 1,
+    Open = 2,
+}
+
+impl From<State> for InterpactState {
+    fn from(state: State) -> InterpactState {
+        match state {
+            State::Closed => InterpactState::Closed,
+            State::HalfOpen => InterpactState::HalfOpen,
+            State::Open => InterpactState::Open,
+        }
+    }
+}
+
+pub struct InterpactBreaker {
+    cb: Arc<CircuitBreaker>,
+}
+
+/// A call admitted by `interpact_breaker_admit`.
+pub struct InterpactAdmission {
+    admission: Admission,
+}
+
+impl InterpactBreaker {
+    /// Hands `cb` to C. The pointer must be released with `interpact_breaker_free`.
+    pub fn into_raw(cb: Arc<CircuitBreaker>) -> *mut InterpactBreaker {
+        Box::into_raw(Box::new(InterpactBreaker { cb }))
+    }
+}
+
+/// Creates a breaker. A `consecutive_failures` of 0 keeps the default trip condition, and zero durations and
+/// `max_requests` mean the same as in `Options`. Returns null if `name` is null or not UTF-8.
+///
+/// # Safety
+///
+/// `name` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_new(
+    name: *const c_char,
+    max_requests: u32,
+    interval_ms: u64,
+    timeout_ms: u64,
+    consecutive_failures: u64,
+) -> *mut InterpactBreaker {
+    if name.is_null() {
+        return ptr::null_mut();
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(name) => name,
+        Err(_) => return ptr::null_mut(),
+    };
+    let cb = CircuitBreaker::new(Options {
+        name,
+        max_requests,
+        interval: time::Duration::from_millis(interval_ms),
+        timeout: time::Duration::from_millis(timeout_ms),
+        trip_policy: if consecutive_failures > 0 {
+            Some(TripPolicy::ConsecutiveFailures(consecutive_failures))
+        } else {
+            None
+        },
+        ..Options::default()
+    });
+    InterpactBreaker::into_raw(Arc::new(cb))
+}
+
+/// Releases a breaker. Null is ignored.
+///
+/// # Safety
+///
+/// `breaker` must be null or a pointer from this API that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_free(breaker: *mut InterpactBreaker) {
+    if !breaker.is_null() {
+        drop(Box::from_raw(breaker));
+    }
+}
+
+/// Whether the circuit is not Open. This admits nothing, so HalfOpen probe limits, maintenance and
+/// `max_in_flight` only apply to calls admitted with `interpact_breaker_admit`.
+///
+/// # Safety
+///
+/// `breaker` must be a live pointer from this API.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_is_call_permitted(breaker: *const InterpactBreaker) -> bool {
+    (*breaker).cb.is_call_permitted()
+}
+
+/// Admits a call, or returns null if the breaker rejects it.
+///
+/// # Safety
+///
+/// `breaker` must be a live pointer from this API.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_admit(breaker: *const InterpactBreaker) -> *mut InterpactAdmission {
+    match DynBreaker::admit(&*(*breaker).cb) {
+        Ok(admission) => Box::into_raw(Box::new(InterpactAdmission { admission })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Records how an admitted call ended and frees `admission`. Null is ignored.
+unsafe fn record(breaker: *const InterpactBreaker, admission: *mut InterpactAdmission, outcome: Outcome) {
+    if !admission.is_null() {
+        let admission = Box::from_raw(admission).admission;
+        DynBreaker::record(&*(*breaker).cb, admission, outcome);
+    }
+}
+
+/// # Safety
+///
+/// `breaker` must be a live pointer from this API, and `admission` null or an admission of that breaker that
+/// has not been recorded or released yet.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_record_success(
+    breaker: *const InterpactBreaker,
+    admission: *mut InterpactAdmission,
+) {
+    record(breaker, admission, Outcome::Success)
+}
+
+/// # Safety
+///
+/// As for `interpact_breaker_record_success`.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_record_failure(
+    breaker: *const InterpactBreaker,
+    admission: *mut InterpactAdmission,
+) {
+    record(breaker, admission, Outcome::Failure)
+}
+
+/// Gives back an admitted call that never ran, without recording an outcome.
+///
+/// # Safety
+///
+/// As for `interpact_breaker_record_success`.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_release(breaker: *const InterpactBreaker, admission: *mut InterpactAdmission) {
+    record(breaker, admission, Outcome::Ignored)
+}
+
+/// # Safety
+///
+/// `breaker` must be a live pointer from this API.
+#[no_mangle]
+pub unsafe extern "C" fn interpact_breaker_state(breaker: *const InterpactBreaker) -> InterpactState {
+    (*breaker).cb.state().into()
+}
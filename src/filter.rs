@@ -0,0 +1,88 @@
+use interpact::{State, Transition};
+use listener::{CallEvent, Listener};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Wraps a listener so it only sees the events it cares about: transitions into given states, events from
+/// breakers whose names match a glob, and at most a `sample` share of per-call events. The state filter does not
+/// apply to per-call events, and transitions are never sampled.
+pub struct Filtered<L> {
+    listener: L,
+    to: Vec<State>,
+    name: Option<String>,
+    sample: f64,
+    seen: AtomicU64,
+}
+
+impl<L: Listener> Filtered<L> {
+    pub fn new(listener: L) -> Filtered<L> {
+        Filtered {
+            listener,
+            to: Vec::new(),
+            name: None,
+            sample: 1.0,
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Only pass transitions into `state`. Can be given more than once.
+    pub fn to(mut self, state: State) -> Filtered<L> {
+        self.to.push(state);
+        self
+    }
+
+    /// Only pass transitions of breakers whose name matches `glob`, where `*` matches any run of characters
+    /// and `?` any single one.
+    pub fn name(mut self, glob: &str) -> Filtered<L> {
+        self.name = Some(glob.into());
+        self
+    }
+
+    /// Pass only a `rate` share of the remaining per-call events, spread evenly rather than at random.
+    /// Transitions are rare and each one matters, so they always pass.
+    pub fn sample(mut self, rate: f64) -> Filtered<L> {
+        self.sample = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    fn sampled(&self) -> bool {
+        if self.sample >= 1.0 {
+            return true;
+        }
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * self.sample).floor() > (n * self.sample).floor()
+    }
+}
+
+impl<L: Listener> Listener for Filtered<L> {
+    fn on_transition(&self, transition: &Transition) {
+        if !self.to.is_empty() && !self.to.contains(&transition.to) {
+            return;
+        }
+        if let Some(ref glob) = self.name {
+            if !matches_glob(glob.as_bytes(), transition.name.as_bytes()) {
+                return;
+            }
+        }
+        self.listener.on_transition(transition);
+    }
+
+    fn on_call(&self, call: &CallEvent) {
+        if let Some(ref glob) = self.name {
+            if !matches_glob(glob.as_bytes(), call.name.as_bytes()) {
+                return;
+            }
+        }
+        if self.sampled() {
+            self.listener.on_call(call);
+        }
+    }
+}
+
+pub(crate) fn matches_glob(glob: &[u8], name: &[u8]) -> bool {
+    match glob.split_first() {
+        None => name.is_empty(),
+        Some((&b'*', rest)) => (0..=name.len()).any(|skip| matches_glob(rest, &name[skip..])),
+        Some((&b'?', rest)) => !name.is_empty() && matches_glob(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && matches_glob(rest, &name[1..]),
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::VecDeque;
+use std::time;
+
+/// How much a flapping breaker is currently dampened: at `level` n the open timeout is doubled n times and
+/// n extra success streaks are needed to close from HalfOpen, with as many extra probes let through to make them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Dampening {
+    pub level: u32,
+    pub timeout: time::Duration,
+    pub success_threshold: u32,
+    pub max_requests: u32,
+}
+
+const MAX_LEVEL: u32 = 6;
+
+pub(crate) struct Flapping {
+    opened_at: VecDeque<time::Instant>,
+    pub(crate) level: u32,
+}
+
+impl Flapping {
+    pub(crate) fn new() -> Flapping {
+        Flapping {
+            opened_at: VecDeque::new(),
+            level: 0,
+        }
+    }
+
+    /// Records a transition to Open. Returns true if that made `threshold` opens within `window`, which
+    /// raises the dampening level.
+    pub(crate) fn opened(&mut self, now: time::Instant, window: time::Duration, threshold: u32) -> bool {
+        while self.opened_at.front().is_some_and(|at| now.duration_since(*at) > window) {
+            self.opened_at.pop_front();
+        }
+        self.opened_at.push_back(now);
+        if self.opened_at.len() < threshold as usize || self.level >= MAX_LEVEL {
+            return false;
+        }
+        self.opened_at.clear();
+        self.level += 1;
+        true
+    }
+
+    /// Returns true if the dampening was lifted because the breaker has not opened for a whole `window`.
+    pub(crate) fn settle(&mut self, now: time::Instant, window: time::Duration) -> bool {
+        if self.level == 0 || self.opened_at.back().is_some_and(|at| now.duration_since(*at) <= window) {
+            return false;
+        }
+        self.opened_at.clear();
+        self.level = 0;
+        true
+    }
+}
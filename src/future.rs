@@ -0,0 +1,158 @@
+use deadline::{self, Deadline};
+use errors;
+use futures::future::{self, Future};
+use futures::task::{Context, Poll};
+use futures_timer::Delay;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use std::pin::Pin;
+use std::time;
+
+pin_project! {
+    /// A protected async call. Admission happens on first poll; if the call is cancelled, either through the
+    /// cancellation future or by being dropped while in flight, it is recorded according to `Options::cancelled_as`.
+    pub struct Call<'a, F, Fut, C> {
+        cb: &'a CircuitBreaker,
+        task: Option<F>,
+        #[pin]
+        future: Option<Fut>,
+        #[pin]
+        cancel: C,
+        #[pin]
+        timeout: Option<Delay>,
+        generation: Option<u64>,
+        started: Option<time::Instant>,
+    }
+
+    impl<'a, F, Fut, C> PinnedDrop for Call<'a, F, Fut, C> {
+        fn drop(this: Pin<&mut Self>) {
+            let this = this.project();
+            if let (Some(generation), Some(started)) = (this.generation.take(), *this.started) {
+                this.cb.after_call(generation, Outcome::Cancelled, started);
+            }
+        }
+    }
+}
+
+impl<'a, F, Fut, C, T, E> Future for Call<'a, F, Fut, C>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    C: Future<Output = ()>,
+{
+    type Output = Result<Result<T, E>, errors::CircuitBreakerError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        if let Some(task) = this.task.take() {
+            *this.generation = Some(this.cb.before_request()?);
+            *this.started = Some(this.cb.now());
+            this.future.set(Some(task()));
+        }
+        let (generation, started) = match (*this.generation, *this.started) {
+            (Some(generation), Some(started)) => (generation, started),
+            _ => panic!("Call polled after completion"),
+        };
+        if this.cancel.poll(cx).is_ready() {
+            *this.generation = None;
+            this.future.set(None);
+            this.cb.after_call(generation, Outcome::Cancelled, started);
+            return Poll::Ready(Err(errors::CircuitBreakerError {
+                kind: errors::CircuitBreakerErrorKind::CancelledError,
+                message: "The call was cancelled".into(),
+            }));
+        }
+        if let Some(timeout) = this.timeout.as_pin_mut() {
+            if timeout.poll(cx).is_ready() {
+                *this.generation = None;
+                this.future.set(None);
+                this.cb.after_call(generation, Outcome::Failure, started);
+                return Poll::Ready(Err(deadline::expired()));
+            }
+        }
+        let result = match this.future.as_pin_mut() {
+            Some(future) => match future.poll(cx) {
+                Poll::Ready(result) => result,
+                Poll::Pending => return Poll::Pending,
+            },
+            None => panic!("Call polled after completion"),
+        };
+        *this.generation = None;
+        this.cb.after_call(generation, Outcome::from_success(result.is_ok()), started);
+        Poll::Ready(Ok(result))
+    }
+}
+
+impl CircuitBreaker {
+    pub fn execute_async<F, Fut, T, E>(&self, task: F) -> Call<'_, F, Fut, future::Pending<()>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.execute_cancellable(future::pending(), task)
+    }
+
+    /// Like `execute_async`, but `cancel` (e.g. tokio-util's `token.cancelled()`) aborts the call when it resolves.
+    pub fn execute_cancellable<F, Fut, C, T, E>(&self, cancel: C, task: F) -> Call<'_, F, Fut, C>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        C: Future<Output = ()>,
+    {
+        Call {
+            cb: self,
+            task: Some(task),
+            future: None,
+            cancel,
+            timeout: None,
+            generation: None,
+            started: None,
+        }
+    }
+
+    /// Like `execute_async`, but calls still running after `timeout` are dropped and recorded as failures, so
+    /// request-reply calls to a responder that has gone away fail fast once the circuit opens.
+    pub fn execute_async_timeout<'a, F, Fut, T, E>(
+        &'a self,
+        timeout: time::Duration,
+        task: F,
+    ) -> impl Future<Output = Result<Result<T, E>, errors::CircuitBreakerError>> + 'a
+    where
+        F: FnOnce() -> Fut + 'a,
+        Fut: Future<Output = Result<T, E>> + 'a,
+        T: 'a,
+        E: 'a,
+    {
+        self.execute_async_with_deadline(self.deadline_after(timeout), future::pending(), |_| task())
+    }
+
+    /// Like `execute_cancellable`, with `deadline` passed to the task and enforced as the call's timeout.
+    /// Calls still running at the deadline are dropped and recorded as failures.
+    pub fn execute_async_with_deadline<'a, F, Fut, C, T, E>(
+        &'a self,
+        deadline: Deadline,
+        cancel: C,
+        task: F,
+    ) -> impl Future<Output = Result<Result<T, E>, errors::CircuitBreakerError>> + 'a
+    where
+        F: FnOnce(Deadline) -> Fut + 'a,
+        Fut: Future<Output = Result<T, E>> + 'a,
+        C: Future<Output = ()> + 'a,
+        T: 'a,
+        E: 'a,
+    {
+        let now = self.now();
+        if deadline.is_expired_at(now) {
+            return future::Either::Left(future::ready(Err(deadline::expired())));
+        }
+        future::Either::Right(Call {
+            cb: self,
+            task: Some(move || task(deadline)),
+            future: None,
+            cancel,
+            timeout: Some(Delay::new(deadline.remaining_at(now))),
+            generation: None,
+            started: None,
+        })
+    }
+}
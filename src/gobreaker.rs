@@ -0,0 +1,153 @@
+//! The API of Go's `sony/gobreaker`, for services ported from Go that should behave exactly as before.
+//! Zero values mean what they mean there: `max_requests` 0 allows one request while HalfOpen, `interval` 0 never
+//! clears the counts while Closed, `timeout` 0 opens for 60 seconds, and no `ready_to_trip` trips after more
+//! than 5 consecutive failures.
+
+use errors;
+use interpact::{self, Counts, Options, State};
+use outcome::Outcome;
+use permit::Permit;
+use std::fmt;
+use std::time;
+
+/// gobreaker's `Settings`.
+#[derive(Default)]
+pub struct Settings<'a> {
+    pub name: &'a str,
+    pub max_requests: u32,
+    pub interval: time::Duration,
+    pub timeout: time::Duration,
+    pub ready_to_trip: Option<fn(counts: Counts) -> bool>,
+    pub on_state_change: Option<fn(name: String, from: State, to: State)>,
+}
+
+impl<'a> Settings<'a> {
+    pub fn options(&self) -> Options<'a> {
+        let mut options = Options {
+            name: self.name,
+            max_requests: self.max_requests,
+            interval: self.interval,
+            timeout: self.timeout,
+            ..Options::default()
+        };
+        if let Some(ready_to_trip) = self.ready_to_trip {
+            options.ready_to_trip = ready_to_trip;
+        }
+        if let Some(on_state_change) = self.on_state_change {
+            options.on_state_change = on_state_change;
+        }
+        options
+    }
+}
+
+/// What `execute` fails with: `ErrOpenState`, `ErrTooManyRequests`, or the request's own error.
+#[derive(Debug)]
+pub enum Error<E> {
+    OpenState,
+    TooManyRequests,
+    Request(E),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::OpenState => write!(f, "circuit breaker is open"),
+            Error::TooManyRequests => write!(f, "too many requests"),
+            Error::Request(ref err) => err.fmt(f),
+        }
+    }
+}
+
+fn rejected<E>(err: &errors::CircuitBreakerError) -> Error<E> {
+    match err.kind {
+        errors::CircuitBreakerErrorKind::TooManyRequestsError => Error::TooManyRequests,
+        _ => Error::OpenState,
+    }
+}
+
+/// gobreaker's `CircuitBreaker`.
+pub struct CircuitBreaker {
+    cb: interpact::CircuitBreaker,
+}
+
+pub fn new_circuit_breaker(settings: Settings) -> CircuitBreaker {
+    CircuitBreaker {
+        cb: interpact::CircuitBreaker::new(settings.options()),
+    }
+}
+
+impl CircuitBreaker {
+    pub fn name(&self) -> &str {
+        self.cb.name()
+    }
+
+    pub fn state(&self) -> State {
+        self.cb.state()
+    }
+
+    pub fn counts(&self) -> Counts {
+        self.cb.counts()
+    }
+
+    pub fn execute<T, E, F>(&self, req: F) -> Result<T, Error<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = Permit::acquire(&self.cb).map_err(|err| rejected(&err))?;
+        let result = req();
+        permit.record(Outcome::from_success(result.is_ok()));
+        result.map_err(Error::Request)
+    }
+
+    /// The breaker underneath, for the features gobreaker does not have.
+    pub fn inner(&self) -> &interpact::CircuitBreaker {
+        &self.cb
+    }
+}
+
+/// gobreaker's `TwoStepCircuitBreaker`: `allow` admits a request and the returned `Done` records how it went.
+pub struct TwoStepCircuitBreaker {
+    cb: interpact::CircuitBreaker,
+}
+
+pub fn new_two_step_circuit_breaker(settings: Settings) -> TwoStepCircuitBreaker {
+    TwoStepCircuitBreaker {
+        cb: interpact::CircuitBreaker::new(settings.options()),
+    }
+}
+
+/// Reports the outcome of a request admitted by `TwoStepCircuitBreaker::allow`. Dropped without `done`, the
+/// request counts as cancelled.
+#[must_use = "an admitted request must be reported with done"]
+pub struct Done<'a> {
+    permit: Permit<&'a interpact::CircuitBreaker>,
+}
+
+impl<'a> Done<'a> {
+    pub fn done(self, success: bool) {
+        self.permit.record(Outcome::from_success(success));
+    }
+}
+
+impl TwoStepCircuitBreaker {
+    pub fn name(&self) -> &str {
+        self.cb.name()
+    }
+
+    pub fn state(&self) -> State {
+        self.cb.state()
+    }
+
+    pub fn counts(&self) -> Counts {
+        self.cb.counts()
+    }
+
+    pub fn allow(&self) -> Result<Done<'_>, Error<()>> {
+        let permit = Permit::acquire(&self.cb).map_err(|err| rejected(&err))?;
+        Ok(Done { permit })
+    }
+
+    pub fn inner(&self) -> &interpact::CircuitBreaker {
+        &self.cb
+    }
+}
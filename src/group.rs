@@ -0,0 +1,68 @@
+use clock::{Clock, SystemClock};
+use poison;
+use std::sync;
+use std::time;
+
+struct Budget {
+    failures: u32,
+    window_start: time::Instant,
+}
+
+/// A failure budget shared by sibling breakers. Every failure recorded by a member spends from the budget;
+/// once it is exhausted within `window`, every member is forced Open on its next call.
+pub struct Group {
+    name: String,
+    pub(crate) max_failures: u32,
+    window: time::Duration,
+    clock: sync::Arc<dyn Clock>,
+    budget: sync::Mutex<Budget>,
+}
+
+impl Group {
+    pub fn new(name: &str, max_failures: u32, window: time::Duration) -> Group {
+        Group::with_clock(name, max_failures, window, sync::Arc::new(SystemClock))
+    }
+
+    /// Like `new`, with the budget's window timed by `clock`, e.g. the one its members use.
+    pub fn with_clock(name: &str, max_failures: u32, window: time::Duration, clock: sync::Arc<dyn Clock>) -> Group {
+        let window_start = clock.now();
+        Group {
+            name: String::from(name),
+            max_failures,
+            window,
+            clock,
+            budget: sync::Mutex::new(Budget {
+                failures: 0,
+                window_start,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn remaining(&self) -> u32 {
+        let budget = self.current();
+        self.max_failures.saturating_sub(budget.failures)
+    }
+
+    pub fn exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub(crate) fn failed(&self) {
+        let mut budget = self.current();
+        budget.failures = budget.failures.saturating_add(1);
+    }
+
+    fn current(&self) -> sync::MutexGuard<'_, Budget> {
+        let mut budget = poison::lock(&self.budget);
+        let now = self.clock.now();
+        if now.duration_since(budget.window_start) >= self.window {
+            budget.failures = 0;
+            budget.window_start = now;
+        }
+        budget
+    }
+}
@@ -0,0 +1,48 @@
+use classify::Classifier;
+use outcome::Outcome;
+use std::collections::HashMap;
+use tonic;
+
+/// Classifies gRPC outcomes by `tonic::Status` code. Codes that point at an unhealthy server (UNAVAILABLE,
+/// DEADLINE_EXCEEDED, RESOURCE_EXHAUSTED, INTERNAL, UNKNOWN, DATA_LOSS) count as failures; codes that describe
+/// the request itself (NOT_FOUND, INVALID_ARGUMENT, ...) are ignored. Individual codes can be overridden.
+#[derive(Debug, Clone, Default)]
+pub struct GrpcClassifier {
+    overrides: HashMap<i32, Outcome>,
+}
+
+impl GrpcClassifier {
+    pub fn new() -> GrpcClassifier {
+        GrpcClassifier::default()
+    }
+
+    pub fn code(mut self, code: tonic::Code, outcome: Outcome) -> GrpcClassifier {
+        self.overrides.insert(code as i32, outcome);
+        self
+    }
+
+    pub fn classify_code(&self, code: tonic::Code) -> Outcome {
+        if let Some(outcome) = self.overrides.get(&(code as i32)) {
+            return *outcome;
+        }
+        match code {
+            tonic::Code::Ok => Outcome::Success,
+            tonic::Code::Unavailable
+            | tonic::Code::DeadlineExceeded
+            | tonic::Code::ResourceExhausted
+            | tonic::Code::Internal
+            | tonic::Code::Unknown
+            | tonic::Code::DataLoss => Outcome::Failure,
+            _ => Outcome::Ignored,
+        }
+    }
+}
+
+impl<T> Classifier<T, tonic::Status> for GrpcClassifier {
+    fn classify(&self, result: &Result<T, tonic::Status>) -> Outcome {
+        match *result {
+            Ok(_) => Outcome::Success,
+            Err(ref status) => self.classify_code(status.code()),
+        }
+    }
+}
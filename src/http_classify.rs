@@ -0,0 +1,71 @@
+use classify::Classifier;
+use http;
+use outcome::Outcome;
+use std::collections::HashMap;
+
+/// Classifies `http::Response`s: 5xx and 429 count as failures, other 4xx are ignored and everything else
+/// succeeds. Errors that produced no response at all are failures. Individual statuses can be overridden.
+#[derive(Debug, Clone, Default)]
+pub struct HttpClassifier {
+    overrides: HashMap<u16, Outcome>,
+    inbound: bool,
+}
+
+impl HttpClassifier {
+    pub fn new() -> HttpClassifier {
+        HttpClassifier::default()
+    }
+
+    /// Classifies responses a server sends, for judging the client that caused them: 4xx count as failures,
+    /// other than 401, 403 and 404, which are ignored, and 5xx are ignored as the server's own doing.
+    pub fn inbound() -> HttpClassifier {
+        HttpClassifier {
+            inbound: true,
+            ..HttpClassifier::default()
+        }
+    }
+
+    pub fn status(mut self, status: http::StatusCode, outcome: Outcome) -> HttpClassifier {
+        self.overrides.insert(status.as_u16(), outcome);
+        self
+    }
+
+    /// Like `classify_status`, for clients that report statuses as plain codes, such as ureq's
+    /// `Error::StatusCode`. Codes that are not valid statuses are failures.
+    pub fn classify_code(&self, code: u16) -> Outcome {
+        match http::StatusCode::from_u16(code) {
+            Ok(status) => self.classify_status(status),
+            Err(_) => Outcome::Failure,
+        }
+    }
+
+    pub fn classify_status(&self, status: http::StatusCode) -> Outcome {
+        if let Some(outcome) = self.overrides.get(&status.as_u16()) {
+            return *outcome;
+        }
+        if self.inbound {
+            return match status {
+                http::StatusCode::UNAUTHORIZED | http::StatusCode::FORBIDDEN | http::StatusCode::NOT_FOUND => Outcome::Ignored,
+                _ if status.is_client_error() => Outcome::Failure,
+                _ if status.is_server_error() => Outcome::Ignored,
+                _ => Outcome::Success,
+            };
+        }
+        if status.is_server_error() || status == http::StatusCode::TOO_MANY_REQUESTS {
+            Outcome::Failure
+        } else if status.is_client_error() {
+            Outcome::Ignored
+        } else {
+            Outcome::Success
+        }
+    }
+}
+
+impl<B, E> Classifier<http::Response<B>, E> for HttpClassifier {
+    fn classify(&self, result: &Result<http::Response<B>, E>) -> Outcome {
+        match *result {
+            Ok(ref response) => self.classify_status(response.status()),
+            Err(_) => Outcome::Failure,
+        }
+    }
+}
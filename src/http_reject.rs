@@ -0,0 +1,17 @@
+use errors;
+use http;
+use interpact::CircuitBreaker;
+
+/// The response for a call `cb` turned away: 503 Service Unavailable, with a `Retry-After` in whole seconds,
+/// rounded up, while the circuit is Open. Server integrations return it as is or copy its parts.
+pub fn rejection(cb: &CircuitBreaker, err: &errors::CircuitBreakerError) -> http::Response<String> {
+    let mut response = http::Response::new(err.message.to_string());
+    *response.status_mut() = http::StatusCode::SERVICE_UNAVAILABLE;
+    if let Some(retry_after) = cb.retry_after() {
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        response
+            .headers_mut()
+            .insert(http::header::RETRY_AFTER, http::HeaderValue::from(secs));
+    }
+    response
+}
@@ -0,0 +1,50 @@
+use classify::Classifier;
+use errors;
+use interpact::CircuitBreaker;
+use keyed::{Keyed, WhenFull};
+use permit::Permit;
+use std::hash::Hash;
+use std::sync;
+
+/// Server-side breakers, one per client identity such as an API key or address, tripped by that client's own
+/// errors and timeouts. A pathological caller is turned away without affecting anyone else. Client identities
+/// are untrusted input, so at most `max_clients` breakers are kept and the least recently seen client is dropped
+/// to make room.
+pub struct Inbound<K> {
+    clients: Keyed<K>,
+}
+
+impl<K: Hash + Eq + Clone> Inbound<K> {
+    pub fn new(factory: fn(client: &K) -> CircuitBreaker, max_clients: usize) -> Inbound<K> {
+        Inbound {
+            clients: Keyed::new(factory).capacity(max_clients, WhenFull::EvictLeastRecentlyUsed),
+        }
+    }
+
+    /// Admits a request from `client`; record the outcome on the permit once the response is known.
+    pub fn admit(&self, client: &K) -> Result<Permit<sync::Arc<CircuitBreaker>>, errors::CircuitBreakerError> {
+        self.clients.permit(client)
+    }
+
+    /// Handles a request from `client`. `classifier` decides what counts against the client, which for
+    /// inbound traffic is usually the reverse of outbound: see `HttpClassifier::inbound`.
+    pub fn execute_with<T, E, C, F>(
+        &self,
+        client: &K,
+        classifier: &C,
+        task: F,
+    ) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        C: Classifier<T, E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = self.admit(client)?;
+        let result = task();
+        permit.record(classifier.classify(&result));
+        Ok(result)
+    }
+
+    pub fn clients(&self) -> &Keyed<K> {
+        &self.clients
+    }
+}
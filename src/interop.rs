@@ -0,0 +1,68 @@
+//! The shapes of the `failsafe` crate's API, so code written against a failsafe breaker can move over one call
+//! site at a time: `call`/`call_with` return failsafe's flattened `Error`, and failsafe-style failure predicates
+//! work as classifiers.
+
+use classify::Classifier;
+use interpact::{CircuitBreaker, State};
+use outcome::Outcome;
+use permit::Permit;
+use std::fmt;
+
+/// failsafe's `Error`: the call was rejected, or it ran and failed with `Inner`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    Inner(E),
+    Rejected,
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Inner(ref err) => err.fmt(f),
+            Error::Rejected => write!(f, "call was rejected"),
+        }
+    }
+}
+
+/// A failsafe-style failure predicate used as a classifier: errors it returns true for are failures, and all
+/// other errors count as successes, as they do in failsafe.
+#[derive(Debug, Clone, Copy)]
+pub struct Predicate<P>(pub P);
+
+impl<T, E, P> Classifier<T, E> for Predicate<P>
+where
+    P: Fn(&E) -> bool,
+{
+    fn classify(&self, result: &Result<T, E>) -> Outcome {
+        match *result {
+            Err(ref err) if (self.0)(err) => Outcome::Failure,
+            _ => Outcome::Success,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Whether the circuit is not Open, as failsafe's `is_call_permitted` reports. Nothing is admitted, so the
+    /// HalfOpen probe limit and load shedding only apply to calls made through `call`.
+    pub fn is_call_permitted(&self) -> bool {
+        self.state() != State::Open
+    }
+
+    pub fn call<T, E, F>(&self, f: F) -> Result<T, Error<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.call_with(Predicate(|_: &E| true), f)
+    }
+
+    pub fn call_with<T, E, C, F>(&self, classifier: C, f: F) -> Result<T, Error<E>>
+    where
+        C: Classifier<T, E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = Permit::acquire(self).map_err(|_| Error::Rejected)?;
+        let result = f();
+        permit.record(classifier.classify(&result));
+        result.map_err(Error::Inner)
+    }
+}
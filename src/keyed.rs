@@ -0,0 +1,249 @@
+use errors;
+use filter;
+use interpact::{CircuitBreaker, Options};
+use overrides::Overrides;
+use poison;
+use snapshot::Snapshot;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+enum Factory<K> {
+    Fn(fn(key: &K) -> CircuitBreaker),
+    Overrides(Overrides, fn(key: &K) -> &str),
+}
+
+/// What a full `Keyed` set does with a key it has no breaker for yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhenFull {
+    /// Keep the set as it is. `try_get` fails, and `get` hands out a breaker that is not kept in the set.
+    Reject,
+    /// Drop the breaker that was used least recently to make room.
+    EvictLeastRecentlyUsed,
+}
+
+struct Entry {
+    cb: sync::Arc<CircuitBreaker>,
+    used: AtomicU64,
+}
+
+/// Named default options for breakers that share a configuration, such as `"slow-dependency"`.
+pub type Template = fn(key: &str) -> Options;
+
+fn ignore_key<K>(_key: &K, _cb: &CircuitBreaker) {}
+
+/// A set of breakers, one per key, created on first use by `factory`.
+pub struct Keyed<K> {
+    factory: Factory<K>,
+    breakers: sync::RwLock<HashMap<K, Entry>>,
+    templates: sync::RwLock<HashMap<String, Template>>,
+    capacity: Option<(usize, WhenFull)>,
+    on_created: fn(key: &K, cb: &CircuitBreaker),
+    on_evicted: fn(key: &K, cb: &CircuitBreaker),
+    clock: AtomicU64,
+    pub(crate) cursor: sync::atomic::AtomicUsize,
+}
+
+impl<K: Hash + Eq + Clone> Keyed<K> {
+    pub fn new(factory: fn(key: &K) -> CircuitBreaker) -> Keyed<K> {
+        Keyed::with_factory(Factory::Fn(factory))
+    }
+
+    /// Creates each breaker from `overrides.options(key)`.
+    pub fn with_overrides(overrides: Overrides) -> Keyed<K>
+    where
+        K: AsRef<str>,
+    {
+        Keyed::with_factory(Factory::Overrides(overrides, K::as_ref))
+    }
+
+    fn with_factory(factory: Factory<K>) -> Keyed<K> {
+        Keyed {
+            factory,
+            breakers: sync::RwLock::new(HashMap::new()),
+            templates: sync::RwLock::new(HashMap::new()),
+            capacity: None,
+            on_created: ignore_key,
+            on_evicted: ignore_key,
+            clock: AtomicU64::new(0),
+            cursor: sync::atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Holds at most `max` breakers, which guards against unbounded growth when keys come from untrusted input.
+    pub fn capacity(mut self, max: usize, when_full: WhenFull) -> Keyed<K> {
+        self.capacity = Some((max, when_full));
+        self
+    }
+
+    /// Called with every breaker added to the set, after it has been added.
+    pub fn on_created(mut self, on_created: fn(key: &K, cb: &CircuitBreaker)) -> Keyed<K> {
+        self.on_created = on_created;
+        self
+    }
+
+    /// Called with every breaker evicted to make room, after it has been removed.
+    pub fn on_evicted(mut self, on_evicted: fn(key: &K, cb: &CircuitBreaker)) -> Keyed<K> {
+        self.on_evicted = on_evicted;
+        self
+    }
+
+    fn create(&self, key: &K) -> CircuitBreaker {
+        match self.factory {
+            Factory::Fn(factory) => factory(key),
+            Factory::Overrides(ref overrides, name) => overrides.breaker(name(key)),
+        }
+    }
+
+    fn touch(&self, entry: &Entry) -> sync::Arc<CircuitBreaker> {
+        entry
+            .used
+            .store(self.clock.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
+        entry.cb.clone()
+    }
+
+    pub fn get(&self, key: &K) -> sync::Arc<CircuitBreaker> {
+        self.try_get(key).unwrap_or_else(|_| sync::Arc::new(self.create(key)))
+    }
+
+    /// Like `get`, but fails instead of handing out an untracked breaker when the set is full and rejects new keys.
+    pub fn try_get(&self, key: &K) -> Result<sync::Arc<CircuitBreaker>, errors::CircuitBreakerError> {
+        self.get_or_create(key, || self.create(key))
+    }
+
+    /// Registers `template` under `name`, replacing any template registered under it before.
+    pub fn template(&self, name: &str, template: Template) {
+        poison::write(&self.templates).insert(name.into(), template);
+    }
+
+    /// The breaker for `key`, created if need be from the template registered as `template` with `apply` run over
+    /// its options. Breakers that already exist are returned as they are. `None` if there is no such template.
+    pub fn get_from_template(
+        &self,
+        key: &K,
+        template: &str,
+        apply: fn(options: &mut Options),
+    ) -> Option<sync::Arc<CircuitBreaker>>
+    where
+        K: AsRef<str>,
+    {
+        let template = *poison::read(&self.templates).get(template)?;
+        let create = || {
+            let mut options = template(key.as_ref());
+            apply(&mut options);
+            CircuitBreaker::new(options)
+        };
+        Some(self.get_or_create(key, create).unwrap_or_else(|_| sync::Arc::new(create())))
+    }
+
+    fn get_or_create<F>(&self, key: &K, create: F) -> Result<sync::Arc<CircuitBreaker>, errors::CircuitBreakerError>
+    where
+        F: FnOnce() -> CircuitBreaker,
+    {
+        if let Some(entry) = poison::read(&self.breakers).get(key) {
+            return Ok(self.touch(entry));
+        }
+        let mut breakers = poison::write(&self.breakers);
+        if let Some(entry) = breakers.get(key) {
+            return Ok(self.touch(entry));
+        }
+        let mut evicted = None;
+        if let Some((max, when_full)) = self.capacity {
+            if breakers.len() >= max {
+                match when_full {
+                    WhenFull::Reject => {
+                        return Err(errors::CircuitBreakerError {
+                            kind: errors::CircuitBreakerErrorKind::CapacityError,
+                            message: "Too many breakers".into(),
+                        })
+                    }
+                    WhenFull::EvictLeastRecentlyUsed => {
+                        let oldest = breakers
+                            .iter()
+                            .min_by_key(|&(_, entry)| entry.used.load(Ordering::Relaxed))
+                            .map(|(key, _)| key.clone());
+                        evicted = oldest.and_then(|oldest| breakers.remove_entry(&oldest));
+                    }
+                }
+            }
+        }
+        let cb = sync::Arc::new(create());
+        let entry = Entry {
+            cb: cb.clone(),
+            used: AtomicU64::new(self.clock.fetch_add(1, Ordering::Relaxed)),
+        };
+        breakers.insert(key.clone(), entry);
+        drop(breakers);
+        if let Some((evicted_key, entry)) = evicted {
+            (self.on_evicted)(&evicted_key, &entry.cb);
+        }
+        (self.on_created)(key, &cb);
+        Ok(cb)
+    }
+
+    /// Drops the breaker for `key`, if there is one. Hooks are not called.
+    pub fn remove(&self, key: &K) -> Option<sync::Arc<CircuitBreaker>> {
+        poison::write(&self.breakers).remove(key).map(|entry| entry.cb)
+    }
+
+    pub fn breakers(&self) -> Vec<(K, sync::Arc<CircuitBreaker>)> {
+        poison::read(&self.breakers)
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.cb.clone()))
+            .collect()
+    }
+
+    /// The breakers carrying the label `name=value`.
+    pub fn labelled(&self, name: &str, value: &str) -> Vec<(K, sync::Arc<CircuitBreaker>)> {
+        poison::read(&self.breakers)
+            .iter()
+            .filter(|&(_, entry)| entry.cb.labels().get(name).is_some_and(|label| label == value))
+            .map(|(key, entry)| (key.clone(), entry.cb.clone()))
+            .collect()
+    }
+
+    /// Iterates over the breakers present when it was called.
+    pub fn iter(&self) -> ::std::vec::IntoIter<(K, sync::Arc<CircuitBreaker>)> {
+        self.breakers().into_iter()
+    }
+
+    pub fn snapshot_all(&self) -> Vec<(K, Snapshot)> {
+        self.iter().map(|(key, cb)| (key, cb.snapshot())).collect()
+    }
+
+    /// Resets every breaker; returns how many there were.
+    pub fn reset_all(&self, reason: Option<&str>) -> usize {
+        let mut reset = 0;
+        for (_, cb) in self.iter() {
+            cb.reset(reason);
+            reset += 1;
+        }
+        reset
+    }
+
+    /// Forces open every breaker whose name matches `glob`, where `*` matches any run of characters and `?` any
+    /// single one. Returns how many were forced open.
+    pub fn force_open_matching(&self, glob: &str, reason: Option<&str>) -> usize {
+        let mut forced = 0;
+        for (_, cb) in self.iter() {
+            if filter::matches_glob(glob.as_bytes(), cb.name().as_bytes()) {
+                cb.force_open(reason);
+                forced += 1;
+            }
+        }
+        forced
+    }
+
+    pub fn keys(&self) -> Vec<K> {
+        poison::read(&self.breakers).keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        poison::read(&self.breakers).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
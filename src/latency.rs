@@ -0,0 +1,233 @@
+use interpact::CircuitBreaker;
+use poison;
+use std::time;
+
+// Bucket `i` holds latencies of up to 2^(i/4) microseconds, so bucket bounds are within 19% of each other and
+// the last one reaches about twelve days.
+const BUCKETS: usize = 161;
+const DEFAULT_WINDOW: time::Duration = time::Duration::from_secs(60);
+
+/// Defines a slow call relative to the breaker's own trailing latency: slower than `factor` times the
+/// `percentile` (0 to 1) of the calls seen over the last one to two `window`s. Only applies once the history
+/// holds `min_samples` calls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SlowCallPercentile {
+    pub percentile: f64,
+    pub factor: f64,
+    pub window: time::Duration,
+    pub min_samples: u64,
+}
+
+impl SlowCallPercentile {
+    /// Replaces a factor that is not a positive number with 1, and keeps the percentile within 0 to 1.
+    pub(crate) fn clamped(self) -> SlowCallPercentile {
+        SlowCallPercentile {
+            percentile: if self.percentile.is_nan() {
+                1.0
+            } else {
+                self.percentile.clamp(0.0, 1.0)
+            },
+            factor: if self.factor.is_finite() && self.factor > 0.0 {
+                self.factor
+            } else {
+                1.0
+            },
+            ..self
+        }
+    }
+}
+
+fn bucket_of(latency: time::Duration) -> usize {
+    let micros = latency.as_secs_f64() * 1e6;
+    if micros <= 1.0 {
+        return 0;
+    }
+    ((micros.log2() * 4.0).ceil() as usize).min(BUCKETS - 1)
+}
+
+pub(crate) fn upper_bound(bucket: usize) -> time::Duration {
+    time::Duration::from_secs_f64((bucket as f64 / 4.0).exp2() / 1e6)
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Histogram {
+    pub(crate) counts: Vec<u64>,
+    pub(crate) total: u64,
+}
+
+impl Histogram {
+    fn new() -> Histogram {
+        Histogram {
+            counts: vec![0; BUCKETS],
+            total: 0,
+        }
+    }
+
+    fn record(&mut self, latency: time::Duration) {
+        let bucket = &mut self.counts[bucket_of(latency)];
+        *bucket = bucket.saturating_add(1);
+        self.total = self.total.saturating_add(1);
+    }
+
+    fn add(&mut self, other: &Histogram) {
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count = count.saturating_add(*other);
+        }
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.total = 0;
+    }
+}
+
+/// Latencies of completed calls over the current and the previous window.
+pub(crate) struct Latencies {
+    current: Histogram,
+    previous: Histogram,
+    rotated: time::Instant,
+    window: time::Duration,
+}
+
+impl Latencies {
+    pub(crate) fn new(now: time::Instant, window: Option<time::Duration>) -> Latencies {
+        Latencies {
+            current: Histogram::new(),
+            previous: Histogram::new(),
+            rotated: now,
+            window: window.unwrap_or(DEFAULT_WINDOW),
+        }
+    }
+
+    fn rotate(&mut self, now: time::Instant) {
+        let elapsed = now.saturating_duration_since(self.rotated);
+        if elapsed < self.window {
+            return;
+        }
+        if elapsed >= self.window * 2 {
+            self.previous.clear();
+        } else {
+            ::std::mem::swap(&mut self.previous, &mut self.current);
+        }
+        self.current.clear();
+        self.rotated = now;
+    }
+
+    pub(crate) fn record(&mut self, now: time::Instant, latency: time::Duration) {
+        self.rotate(now);
+        self.current.record(latency);
+    }
+
+    pub(crate) fn trailing(&mut self, now: time::Instant) -> Histogram {
+        self.rotate(now);
+        let mut trailing = self.previous.clone();
+        trailing.add(&self.current);
+        trailing
+    }
+
+    /// Reads the percentile across both windows in place, since this runs for every recorded call.
+    pub(crate) fn is_slow(&mut self, now: time::Instant, latency: time::Duration, slow: SlowCallPercentile) -> bool {
+        self.rotate(now);
+        let total = self.previous.total.saturating_add(self.current.total);
+        if total < slow.min_samples.max(1) {
+            return false;
+        }
+        let rank = ((total as f64 * slow.percentile).ceil() as u64).clamp(1, total);
+        let mut seen = 0u64;
+        let bucket = self
+            .previous
+            .counts
+            .iter()
+            .zip(&self.current.counts)
+            .position(|(previous, current)| {
+                seen = seen.saturating_add(previous.saturating_add(*current));
+                seen >= rank
+            });
+        // Compared in seconds, so that a large factor cannot overflow a Duration.
+        bucket.is_some_and(|bucket| latency.as_secs_f64() > upper_bound(bucket).as_secs_f64() * slow.factor)
+    }
+}
+
+/// A latency bucket: the calls that took more than the previous bucket's bound, up to `upper_bound_nanos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bucket {
+    pub upper_bound_nanos: u64,
+    pub count: u64,
+}
+
+/// The latencies of a breaker's recent calls, as non-empty buckets in ascending order. Bucket bounds are the
+/// same for every breaker, so snapshots from many instances can be merged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HistogramSnapshot {
+    pub buckets: Vec<Bucket>,
+    pub total: u64,
+}
+
+impl HistogramSnapshot {
+    pub fn merge(&mut self, other: &HistogramSnapshot) {
+        let mut merged = Vec::with_capacity(self.buckets.len() + other.buckets.len());
+        let (mut ours, mut theirs) = (self.buckets.iter().peekable(), other.buckets.iter().peekable());
+        loop {
+            let next = match (ours.peek(), theirs.peek()) {
+                (Some(a), Some(b)) if a.upper_bound_nanos == b.upper_bound_nanos => {
+                    let count = a.count.saturating_add(b.count);
+                    ours.next();
+                    theirs.next().map(|bucket| Bucket { count, ..*bucket })
+                }
+                (Some(a), Some(b)) if a.upper_bound_nanos < b.upper_bound_nanos => ours.next().cloned(),
+                (Some(_), Some(_)) | (None, Some(_)) => theirs.next().cloned(),
+                (Some(_), None) => ours.next().cloned(),
+                (None, None) => break,
+            };
+            merged.extend(next);
+        }
+        self.buckets = merged;
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    /// The upper bound of the bucket holding the `percentile` (0 to 1) call.
+    pub fn percentile(&self, percentile: f64) -> Option<time::Duration> {
+        if self.total == 0 {
+            return None;
+        }
+        let rank = ((self.total as f64 * percentile).ceil() as u64).clamp(1, self.total);
+        let mut seen = 0;
+        self.buckets.iter().find_map(|bucket| {
+            seen += bucket.count;
+            if seen >= rank {
+                Some(time::Duration::from_nanos(bucket.upper_bound_nanos))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl<'a> From<&'a Histogram> for HistogramSnapshot {
+    fn from(histogram: &'a Histogram) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: histogram
+                .counts
+                .iter()
+                .enumerate()
+                .filter(|&(_, &count)| count > 0)
+                .map(|(bucket, &count)| Bucket {
+                    upper_bound_nanos: upper_bound(bucket).as_nanos() as u64,
+                    count,
+                })
+                .collect(),
+            total: histogram.total,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// The latencies of calls completed over the last one to two latency windows.
+    pub fn latency_histogram(&self) -> HistogramSnapshot {
+        HistogramSnapshot::from(&poison::lock(&self.latencies).trailing(self.now()))
+    }
+}
@@ -1,25 +1,271 @@
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "async")]
+extern crate futures_timer;
+#[cfg(feature = "http")]
+extern crate http;
+#[cfg(feature = "async")]
+#[macro_use]
+extern crate pin_project_lite;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "tonic")]
+extern crate tonic;
+
+pub mod adaptive;
+pub mod audit;
+pub mod aws_classify;
+pub mod batch;
+#[cfg(feature = "tokio")]
+pub mod blocking;
+pub mod buffer;
+pub mod builder;
+pub mod classify;
+pub mod clock;
+pub mod command;
+pub mod compat;
+pub mod composite;
+pub mod config;
+pub mod context;
+pub mod deadline;
+pub mod delta;
+pub mod dot;
+pub mod dynamic;
+pub mod env;
+pub mod erased;
 pub mod errors;
+pub mod events;
+pub mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod flapping;
+#[cfg(feature = "async")]
+pub mod future;
+pub mod gobreaker;
+pub mod group;
+#[cfg(feature = "tonic")]
+pub mod grpc_classify;
+#[cfg(feature = "http")]
+pub mod http_classify;
+#[cfg(feature = "http")]
+pub mod http_reject;
+pub mod inbound;
+pub mod interop;
+pub mod keyed;
+pub mod latency;
+pub mod listener;
+pub mod logging;
+pub mod maintenance;
+pub mod monitor;
+pub mod outcome;
+pub mod outlier;
+pub mod overrides;
+pub mod permit;
+pub mod phi;
+pub mod pick;
+mod poison;
+pub mod priority;
+pub mod probe;
+pub mod producer;
+pub mod profile;
+pub mod recording;
+pub mod replay;
+pub mod reporter;
+pub mod result_ext;
+pub mod retry;
+pub mod rng;
+pub mod scatter;
+pub mod sentry_hook;
+mod shard;
+#[cfg(feature = "async")]
+pub mod sim;
+pub mod snapshot;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod tenant;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod ticker;
+pub mod watchdog;
+#[cfg(feature = "serde")]
+pub mod wire;
+
+pub use adaptive::AdaptiveTimeout;
+pub use audit::{AuditAction, AuditEntry};
+pub use aws_classify::AwsClassifier;
+pub use batch::{Batch, BatchAdmission, BatchSummary};
+pub use buffer::Buffering;
+pub use builder::Builder;
+pub use classify::{ByError, Classifier, IoErrorClassifier, IoSourceClassifier};
+pub use clock::{Clock, ManualClock};
+pub use composite::Composite;
+pub use config::Config;
+pub use context::Context;
+pub use deadline::Deadline;
+pub use delta::{DeltaCursor, Totals};
+pub use dot::Dot;
+pub use dynamic::{Admission, DynBreaker};
+pub use erased::ErasedBreaker;
+pub use events::{Dispatched, Events, Overflow};
+pub use extract::KeyExtractor;
+pub use filter::Filtered;
+pub use flapping::Dampening;
+#[cfg(feature = "async")]
+pub use future::Call;
+pub use group::Group;
+#[cfg(feature = "tonic")]
+pub use grpc_classify::GrpcClassifier;
+#[cfg(feature = "http")]
+pub use http_classify::HttpClassifier;
+pub use inbound::Inbound;
+pub use interpact::{
+    default_ready_to_trip, CancelledAs, CircuitBreaker, Counts, Options, Reason, State, Transition, TripPolicy,
+};
+pub use keyed::{Keyed, Template, WhenFull};
+pub use latency::{Bucket, HistogramSnapshot, SlowCallPercentile};
+pub use listener::{CallEvent, Listener, ListenerHandle};
+pub use logging::{Logged, Record, Structured};
+pub use maintenance::{Maintenance, MaintenanceWindow};
+pub use monitor::Monitor;
+pub use outcome::Outcome;
+pub use outlier::{OutlierDetector, OutlierOptions};
+pub use overrides::{KeyPattern, Overrides};
+pub use permit::Permit;
+pub use phi::PhiAccrual;
+pub use pick::Pick;
+pub use priority::Priority;
+pub use probe::ProbeRate;
+pub use producer::Producer;
+pub use profile::{Profile, ProfileWindow};
+pub use recording::Recording;
+pub use replay::{Recorder, Replay, Trace};
+pub use reporter::{Reporter, Sink};
+pub use result_ext::ResultExt;
+pub use retry::RetryBudget;
+pub use rng::{Rng, XorShift};
+pub use scatter::Gathered;
+pub use sentry_hook::{Alert, SentryHook};
+#[cfg(feature = "async")]
+pub use sim::Simulation;
+pub use snapshot::Snapshot;
+#[cfg(feature = "async")]
+pub use stream::StreamExt;
+#[cfg(feature = "async")]
+pub use ticker::SnapshotStream;
+pub use ticker::Snapshots;
+pub use watchdog::{Escalation, Watchdog};
+#[cfg(feature = "serde")]
+pub use wire::{Envelope, Shipper};
 
-#[allow(dead_code)]
 mod interpact {
-    use std::time;
-    use std::sync;
+    use audit::{AuditAction, AuditLog};
+    use buffer::{Buffered, Buffering, Buffers};
+    use clock::{Clock, SystemClock};
+    use context::Context;
+    use delta::Totals;
     use errors;
+    use flapping::{Dampening, Flapping};
+    use group::Group;
+    use latency::{Latencies, SlowCallPercentile};
+    use listener::{CallEvent, Listeners};
+    use maintenance::{self, Maintenance, MaintenanceWindow};
+    use outcome::Outcome;
+    use permit::Permit;
+    use phi::PhiAccrual;
+    use poison;
+    use priority::Priority;
+    use probe::{ProbeBucket, ProbeRate};
+    use profile::{Profile, Profiles, DEFAULT_PROFILE};
+    use rng::{Rng, XorShift};
+    use shard::Shards;
+    use snapshot::Snapshot;
+    use std::cmp;
+    use std::collections;
+    use std::fmt;
+    use std::panic;
+    use std::str;
+    use std::sync;
+    use std::time;
 
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
     pub enum State {
         Closed,
         Open,
         HalfOpen,
     }
 
-    #[derive(Debug)]
+    impl fmt::Display for State {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(match *self {
+                State::Closed => "closed",
+                State::Open => "open",
+                State::HalfOpen => "half_open",
+            })
+        }
+    }
+
+    impl str::FromStr for State {
+        type Err = errors::ParseStateError;
+
+        /// Accepts the `Display` form in any case, with "half-open" and "halfopen" as aliases.
+        fn from_str(s: &str) -> Result<State, errors::ParseStateError> {
+            match s.to_ascii_lowercase().as_str() {
+                "closed" => Ok(State::Closed),
+                "open" => Ok(State::Open),
+                "half_open" | "half-open" | "halfopen" => Ok(State::HalfOpen),
+                _ => Err(errors::ParseStateError { input: s.into() }),
+            }
+        }
+    }
+
+    /// Why a breaker changed its state.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+    pub enum Reason {
+        Tripped,
+        OpenTimeoutElapsed,
+        ProbesSucceeded,
+        ProbeFailed,
+        HalfOpenExpired,
+        ParentOpen,
+        GroupExhausted,
+        /// An operator called `force_open` or `force_closed`.
+        Forced,
+        /// An operator called `reset`.
+        Reset,
+    }
+
+    /// A state change, as reported to `on_transition`, in a form that can be stored or shipped elsewhere.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Transition {
+        pub name: String,
+        pub labels: collections::BTreeMap<String, String>,
+        pub from: State,
+        pub to: State,
+        pub reason: Reason,
+        /// The generation the breaker entered with this transition.
+        pub generation: u64,
+    }
+
+    /// Counters saturate rather than wrap, so long-lived breakers never see them roll over.
+    #[derive(Debug, Clone, Copy)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct Counts {
-        requests: u32,
-        total_successes: u32,
-        total_failures: u32,
-        consecutive_successes: u32,
-        consecutive_failures: u32,
+        requests: u64,
+        total_successes: u64,
+        total_failures: u64,
+        consecutive_successes: u64,
+        consecutive_failures: u64,
+        weighted_failures: f64,
+        slow_calls: u64,
+        rejected: u64,
+        not_permitted: u64,
     }
 
     impl Counts {
@@ -30,22 +276,101 @@ mod interpact {
                 total_failures: 0,
                 consecutive_failures: 0,
                 consecutive_successes: 0,
+                weighted_failures: 0.0,
+                slow_calls: 0,
+                rejected: 0,
+                not_permitted: 0,
+            }
+        }
+
+        pub fn requests(&self) -> u64 {
+            self.requests
+        }
+
+        pub fn total_successes(&self) -> u64 {
+            self.total_successes
+        }
+
+        pub fn total_failures(&self) -> u64 {
+            self.total_failures
+        }
+
+        pub fn consecutive_successes(&self) -> u64 {
+            self.consecutive_successes
+        }
+
+        pub fn consecutive_failures(&self) -> u64 {
+            self.consecutive_failures
+        }
+
+        /// The sum of the weights of all failures, each counting 1.0 unless a classifier weighted it.
+        pub fn weighted_failures(&self) -> f64 {
+            self.weighted_failures
+        }
+
+        /// Successes judged slow by `Options::slow_call_duration` or `Options::slow_call_percentile`.
+        pub fn slow_calls(&self) -> u64 {
+            self.slow_calls
+        }
+
+        /// The share of slow calls among all recorded outcomes.
+        pub fn slow_call_rate(&self) -> f64 {
+            let total = self.total_successes.saturating_add(self.total_failures);
+            if total > 0 {
+                self.slow_calls as f64 / total as f64
+            } else {
+                0.0
+            }
+        }
+
+        /// Calls short-circuited because the breaker was Open.
+        pub fn rejected(&self) -> u64 {
+            self.rejected
+        }
+
+        /// Calls turned away because the HalfOpen probe limit was reached.
+        pub fn not_permitted(&self) -> u64 {
+            self.not_permitted
+        }
+
+        /// The share of weighted failures among all recorded outcomes.
+        pub fn failure_rate(&self) -> f64 {
+            let total = self.total_successes as f64 + self.weighted_failures;
+            if total > 0.0 {
+                self.weighted_failures / total
+            } else {
+                0.0
             }
         }
 
         fn requested(&mut self) {
-            self.requests += 1;
+            self.requests = self.requests.saturating_add(1);
         }
 
-        fn failed(&mut self) {
-            self.total_failures += 1;
-            self.consecutive_failures += 1;
+        fn failed(&mut self, weight: f64) {
+            self.total_failures = self.total_failures.saturating_add(1);
+            self.weighted_failures += weight;
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
             self.consecutive_successes = 0;
         }
 
-        fn succeeded(&mut self) {
-            self.total_successes += 1;
-            self.consecutive_successes += 1;
+        fn succeeded(&mut self, slow: bool) {
+            self.total_successes = self.total_successes.saturating_add(1);
+            if slow {
+                self.slow_calls = self.slow_calls.saturating_add(1);
+            }
+            self.consecutive_successes = self.consecutive_successes.saturating_add(1);
+            self.consecutive_failures = 0;
+        }
+
+        /// Adds successes that were counted elsewhere, as though they had been recorded one by one.
+        fn absorb(&mut self, successes: u64, slow: u64) {
+            if successes == 0 {
+                return;
+            }
+            self.total_successes = self.total_successes.saturating_add(successes);
+            self.slow_calls = self.slow_calls.saturating_add(slow);
+            self.consecutive_successes = self.consecutive_successes.saturating_add(successes);
             self.consecutive_failures = 0;
         }
 
@@ -55,138 +380,1230 @@ mod interpact {
             self.total_successes = 0;
             self.consecutive_failures = 0;
             self.consecutive_successes = 0;
+            self.weighted_failures = 0.0;
+            self.slow_calls = 0;
+            self.rejected = 0;
+            self.not_permitted = 0;
         }
     }
 
-    fn default_ready_to_trip(counts: Counts) -> bool {
+    /// The generation handed out for calls whose outcome must not be recorded, e.g. during a shadow maintenance window.
+    const UNRECORDED: u64 = u64::MAX;
+
+    fn too_many_requests() -> errors::CircuitBreakerError {
+        errors::CircuitBreakerError {
+            kind: errors::CircuitBreakerErrorKind::TooManyRequestsError,
+            message: "Maximum requests limit has reached while the CircuitBreaker is HalfOpen".into(),
+        }
+    }
+
+    pub fn default_ready_to_trip(counts: Counts) -> bool {
         counts.consecutive_failures > 5
     }
 
+    /// When a Closed breaker trips.
+    #[derive(Debug, Clone, Copy)]
+    pub enum TripPolicy {
+        Custom(fn(counts: Counts) -> bool),
+        ConsecutiveFailures(u64),
+        /// Trip once the failure rate reaches `rate`, but only after `min_requests` calls in the window.
+        FailureRate {
+            rate: f64,
+            min_requests: u64,
+        },
+    }
+
+    impl TripPolicy {
+        pub fn should_trip(&self, counts: Counts) -> bool {
+            match *self {
+                TripPolicy::Custom(ready_to_trip) => ready_to_trip(counts),
+                TripPolicy::ConsecutiveFailures(threshold) => counts.consecutive_failures >= threshold,
+                TripPolicy::FailureRate { rate, min_requests } => {
+                    counts.requests >= min_requests && counts.failure_rate() >= rate
+                }
+            }
+        }
+    }
+
+    fn ignore_state_change(_name: String, _from: State, _to: State) {}
+
+    fn ignore_transition(_name: String, _from: State, _to: State, _reason: Reason) {}
+
+    fn ignore_idle(_name: String) {}
+
+    fn ignore_dampening(_name: String, _dampening: Dampening) {}
+
+    fn ignore_callback_panic(_name: String, _message: String) {}
+
+    fn ignore_call(_name: &str, _duration: time::Duration, _context: &Context) {}
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+    pub enum CancelledAs {
+        Ignored,
+        Failure,
+        Success,
+    }
+
     pub struct Options<'a> {
         pub name: &'a str,
+        /// Key-value dimensions such as `("service", "payments")`, carried into snapshots and events.
+        pub labels: &'a [(&'a str, &'a str)],
         pub max_requests: u32,
         pub success_threshold: Option<u32>,
         pub interval: time::Duration,
         pub timeout: time::Duration,
+        /// Lengthens every open timeout by a random share of up to this fraction, between 0 and 1, so that breakers
+        /// which tripped together do not all probe at the same moment.
+        pub timeout_jitter: f64,
+        /// The randomness behind `timeout_jitter`; defaults to an `XorShift` seeded per process.
+        pub rng: Option<Box<dyn Rng>>,
+        /// The time source for windows, timeouts and latency history; defaults to the system clock.
+        pub clock: Option<sync::Arc<dyn Clock>>,
+        pub min_open_duration: time::Duration,
+        pub max_half_open_duration: time::Duration,
         pub ready_to_trip: fn(counts: Counts) -> bool,
+        /// Takes precedence over `ready_to_trip` when set.
+        pub trip_policy: Option<TripPolicy>,
+        /// A short window evaluated alongside `interval` to catch sharp spikes; zero disables it. The breaker
+        /// trips if either `ready_to_trip` over the interval or `fast_ready_to_trip` over this window says so.
+        pub fast_window: time::Duration,
+        pub fast_ready_to_trip: fn(counts: Counts) -> bool,
         pub on_state_change: fn(name: String, from: State, to: State),
+        pub on_transition: fn(name: String, from: State, to: State, reason: Reason),
+        pub cancelled_as: CancelledAs,
+        pub shed_below: Priority,
+        pub degraded_failures: Option<u32>,
+        /// How many calls may wait up to `max_wait` for a HalfOpen permit instead of being rejected. Waiting
+        /// blocks on real time, so it is turned off on a clock that does not advance by itself.
+        pub max_waiting: usize,
+        /// Sheds new calls with an `OverloadedError` while this many admitted calls have not finished yet.
+        pub max_in_flight: Option<u32>,
+        /// Rate-shapes HalfOpen probes; when set, it takes the place of `max_requests` in admitting them.
+        pub probe_rate: Option<ProbeRate>,
+        /// Sheds new calls with an `OverloadedError` while this many calls wait for a permit.
+        pub max_queue_depth: Option<usize>,
+        pub max_wait: time::Duration,
+        pub max_requests_per_tenant: Option<u32>,
+        pub parent: Option<sync::Arc<CircuitBreaker>>,
+        pub group: Option<sync::Arc<Group>>,
+        pub phi_threshold: Option<f64>,
+        pub flap_window: time::Duration,
+        pub flap_threshold: u32,
+        pub on_dampening: fn(name: String, dampening: Dampening),
+        pub idle_reset: time::Duration,
+        pub on_idle: fn(name: String),
+        /// Called with the panic message when a callback or listener panics.
+        pub on_callback_panic: fn(name: String, message: String),
+        /// Called after each call run through `execute` and its variants, with how long the call took.
+        /// Successful calls taking at least this long are recorded as `Outcome::Slow`; zero disables this.
+        pub slow_call_duration: time::Duration,
+        /// Also counts successes as slow when they are slower than a multiple of a trailing latency percentile.
+        pub slow_call_percentile: Option<SlowCallPercentile>,
+        pub on_success: fn(name: &str, duration: time::Duration, context: &Context),
+        pub on_failure: fn(name: &str, duration: time::Duration, context: &Context),
+        /// How many of the latest transitions to keep for `Monitor::history`.
+        pub history: usize,
+        /// Stripes the success counters of a Closed breaker over this many shards, so that successes are counted
+        /// without the breaker's lock and only added up when it is next taken. Until then `counts` lags behind,
+        /// and a success that races a transition may land in the next window. 0 keeps the plain counters.
+        pub counter_shards: usize,
+        /// Buffers outcomes per thread while Closed instead of recording each under the breaker's lock.
+        pub buffering: Option<Buffering>,
+    }
+
+    impl<'a> Default for Options<'a> {
+        fn default() -> Options<'a> {
+            Options {
+                name: "",
+                labels: &[],
+                max_requests: 1,
+                success_threshold: None,
+                interval: time::Duration::from_secs(0),
+                timeout: time::Duration::from_secs(60),
+                timeout_jitter: 0.0,
+                rng: None,
+                clock: None,
+                min_open_duration: time::Duration::from_secs(0),
+                max_half_open_duration: time::Duration::from_secs(0),
+                ready_to_trip: default_ready_to_trip,
+                trip_policy: None,
+                fast_window: time::Duration::from_secs(0),
+                fast_ready_to_trip: default_ready_to_trip,
+                on_state_change: ignore_state_change,
+                on_transition: ignore_transition,
+                cancelled_as: CancelledAs::Ignored,
+                shed_below: Priority::Low,
+                degraded_failures: None,
+                max_waiting: 0,
+                max_in_flight: None,
+                probe_rate: None,
+                max_queue_depth: None,
+                max_wait: time::Duration::from_secs(0),
+                max_requests_per_tenant: None,
+                parent: None,
+                group: None,
+                phi_threshold: None,
+                flap_window: time::Duration::from_secs(0),
+                flap_threshold: 3,
+                on_dampening: ignore_dampening,
+                idle_reset: time::Duration::from_secs(0),
+                on_idle: ignore_idle,
+                on_callback_panic: ignore_callback_panic,
+                slow_call_duration: time::Duration::from_secs(0),
+                slow_call_percentile: None,
+                on_success: ignore_call,
+                on_failure: ignore_call,
+                history: 0,
+                counter_shards: 0,
+                buffering: None,
+            }
+        }
+    }
+
+    fn invalid(field: &'static str, message: &str) -> Result<(), errors::ConfigError> {
+        Err(errors::ConfigError {
+            field,
+            message: message.into(),
+        })
+    }
+
+    impl<'a> Options<'a> {
+        /// Checks for combinations that `CircuitBreaker::new` would otherwise quietly adjust or that could never
+        /// behave as intended.
+        pub fn validate(&self) -> Result<(), errors::ConfigError> {
+            let zero = time::Duration::from_secs(0);
+            if self.max_requests == 0 {
+                return invalid("max_requests", "must allow at least one request while HalfOpen");
+            }
+            if self.timeout == zero {
+                return invalid("timeout", "must be greater than zero");
+            }
+            if !(0.0..=1.0).contains(&self.timeout_jitter) {
+                return invalid("timeout_jitter", "must be between 0 and 1");
+            }
+            match self.success_threshold {
+                Some(0) => return invalid("success_threshold", "must be at least one"),
+                Some(threshold) if threshold > self.max_requests => {
+                    return invalid("success_threshold", "must not exceed max_requests");
+                }
+                _ => {}
+            }
+            if let Some(TripPolicy::ConsecutiveFailures(0)) = self.trip_policy {
+                return invalid("trip_policy", "must need at least one consecutive failure to trip");
+            }
+            if let Some(TripPolicy::FailureRate { rate, .. }) = self.trip_policy {
+                if rate.is_nan() || rate <= 0.0 || rate > 1.0 {
+                    return invalid("trip_policy", "the failure rate must be above 0 and at most 1");
+                }
+            }
+            if self.fast_window > zero && self.interval > zero && self.fast_window >= self.interval {
+                return invalid("fast_window", "must be shorter than interval");
+            }
+            if self.max_half_open_duration > zero && self.max_half_open_duration < self.min_open_duration {
+                return invalid("max_half_open_duration", "must not be shorter than min_open_duration");
+            }
+            if self.degraded_failures == Some(0) {
+                return invalid("degraded_failures", "must be at least one");
+            }
+            if self.max_waiting > 0 && self.max_wait == zero {
+                return invalid("max_wait", "must be greater than zero when max_waiting is set");
+            }
+            if self.max_waiting > 0 && self.clock.as_ref().is_some_and(|clock| !clock.advances()) {
+                return invalid(
+                    "max_waiting",
+                    "cannot wait for a permit on a clock that only moves when told to",
+                );
+            }
+            if self.group.as_ref().is_some_and(|group| group.max_failures == 0) {
+                return invalid("group", "must allow at least one failure");
+            }
+            if self.max_in_flight == Some(0) {
+                return invalid("max_in_flight", "must be at least one");
+            }
+            if self.probe_rate.is_some_and(|rate| rate.probes == 0 || rate.per == zero) {
+                return invalid("probe_rate", "must allow at least one probe per non-zero period");
+            }
+            if let Some(slow) = self.slow_call_percentile {
+                if !(slow.percentile > 0.0 && slow.percentile <= 1.0) {
+                    return invalid("slow_call_percentile", "the percentile must be above 0 and at most 1");
+                }
+                if !(slow.factor.is_finite() && slow.factor > 0.0) || slow.window == zero {
+                    return invalid(
+                        "slow_call_percentile",
+                        "the factor must be a positive number and the window greater than zero",
+                    );
+                }
+            }
+            if self.max_queue_depth == Some(0) {
+                return invalid("max_queue_depth", "must be at least one");
+            }
+            if self.max_requests_per_tenant == Some(0) {
+                return invalid("max_requests_per_tenant", "must be at least one");
+            }
+            if self
+                .phi_threshold
+                .is_some_and(|threshold| threshold.is_nan() || threshold <= 0.0)
+            {
+                return invalid("phi_threshold", "must be a positive number");
+            }
+            if self.flap_window > zero && self.flap_threshold == 0 {
+                return invalid("flap_threshold", "must be at least one when flap_window is set");
+            }
+            if self.counter_shards > 0 && self.phi_threshold.is_some() {
+                return invalid(
+                    "counter_shards",
+                    "cannot be combined with phi_threshold, which sees every success",
+                );
+            }
+            if let Some(buffering) = self.buffering {
+                if buffering.max_outcomes == 0 || buffering.max_delay == zero {
+                    return invalid("buffering", "must buffer at least one outcome for a non-zero delay");
+                }
+                if self.phi_threshold.is_some() {
+                    return invalid("buffering", "cannot be combined with phi_threshold, which sees every success");
+                }
+            }
+            Ok(())
+        }
+    }
+
+    pub(crate) struct Inner {
+        state: State,
+        generation: u64,
+        counts: Counts,
+        expires: Option<time::Instant>,
+        fast: Counts,
+        fast_expires: Option<time::Instant>,
+        waiting: collections::VecDeque<u64>,
+        next_ticket: u64,
+        tenants: collections::HashMap<String, u32>,
+        phi: PhiAccrual,
+        flapping: Flapping,
+        last_call: Option<time::Instant>,
+        pub(crate) profiles: Profiles,
+        pub(crate) history: collections::VecDeque<Transition>,
+        pub(crate) totals: Totals,
+        forced: bool,
+        rng: Box<dyn Rng>,
+        probes: ProbeBucket,
+    }
+
+    impl Inner {
+        fn requested(&mut self) {
+            self.counts.requested();
+            self.fast.requested();
+            self.totals.requests = self.totals.requests.saturating_add(1);
+        }
     }
 
     pub struct CircuitBreaker {
         name: String,
-        max_requests: u32,
-        success_threshold: u32,
-        interval: time::Duration,
-        timeout: time::Duration,
-        ready_to_trip: fn(counts: Counts) -> bool,
+        labels: collections::BTreeMap<String, String>,
+        timeout_jitter: f64,
+        min_open_duration: time::Duration,
+        max_half_open_duration: time::Duration,
+        fast_window: time::Duration,
+        fast_ready_to_trip: fn(counts: Counts) -> bool,
         on_state_change: fn(name: String, from: State, to: State),
-        state: sync::Mutex<State>,
-        // generation: u64,
-        counts: Counts,
-        expires: Option<time::Instant>,
+        on_transition: fn(name: String, from: State, to: State, reason: Reason),
+        cancelled_as: CancelledAs,
+        shed_below: Priority,
+        degraded_failures: Option<u32>,
+        max_waiting: usize,
+        max_in_flight: Option<u32>,
+        probe_rate: Option<ProbeRate>,
+        max_queue_depth: Option<usize>,
+        in_flight: sync::atomic::AtomicUsize,
+        max_wait: time::Duration,
+        max_requests_per_tenant: Option<u32>,
+        parent: Option<sync::Arc<CircuitBreaker>>,
+        group: Option<sync::Arc<Group>>,
+        phi_threshold: Option<f64>,
+        flap_window: time::Duration,
+        flap_threshold: u32,
+        on_dampening: fn(name: String, dampening: Dampening),
+        idle_reset: time::Duration,
+        on_idle: fn(name: String),
+        on_callback_panic: fn(name: String, message: String),
+        slow_call_duration: time::Duration,
+        slow_call_percentile: Option<SlowCallPercentile>,
+        pub(crate) latencies: sync::Mutex<Latencies>,
+        on_success: fn(name: &str, duration: time::Duration, context: &Context),
+        on_failure: fn(name: &str, duration: time::Duration, context: &Context),
+        history: usize,
+        permits: sync::Condvar,
+        pub(crate) maintenance_windows: sync::Mutex<Vec<MaintenanceWindow>>,
+        pub(crate) listeners: Listeners,
+        pub(crate) audit_log: AuditLog,
+        paused: sync::atomic::AtomicBool,
+        clock: sync::Arc<dyn Clock>,
+        shards: Option<Shards>,
+        buffers: Option<Buffers>,
+        /// The generation while Closed, so a success can be sharded without taking the lock; `UNRECORDED` otherwise.
+        closed_generation: sync::atomic::AtomicU64,
+        inner: sync::Mutex<Inner>,
     }
 
     impl CircuitBreaker {
         pub fn new(o: Options) -> CircuitBreaker {
             let cb_name = String::from(o.name);
-            let mr = if o.max_requests == 0 {
-                o.max_requests
-            } else {
-                1
-            };
+            let clock = o.clock.unwrap_or_else(|| sync::Arc::new(SystemClock));
+            let now = clock.now();
+            let mr = if o.max_requests == 0 { 1 } else { o.max_requests };
+            // Phi accrual needs a heartbeat from every success, so outcomes cannot bypass the lock.
+            let heartbeats = o.phi_threshold.is_some();
 
-            CircuitBreaker {
+            let cb = CircuitBreaker {
                 name: cb_name,
-                max_requests: mr,
-                success_threshold: o.success_threshold.unwrap_or(mr),
-                interval: o.interval,
-                timeout: if o.timeout > time::Duration::from_secs(0) {
-                    o.timeout
+                labels: o.labels.iter().map(|&(key, value)| (key.into(), value.into())).collect(),
+                timeout_jitter: if o.timeout_jitter.is_nan() {
+                    0.0
                 } else {
-                    time::Duration::from_secs(60)
+                    o.timeout_jitter.clamp(0.0, 1.0)
                 },
-                ready_to_trip: o.ready_to_trip,
+                min_open_duration: o.min_open_duration,
+                max_half_open_duration: o.max_half_open_duration,
+                fast_window: o.fast_window,
+                fast_ready_to_trip: o.fast_ready_to_trip,
                 on_state_change: o.on_state_change,
-                state: sync::Mutex::new(State::Closed),
-                counts: Counts::new(),
-                expires: None,
+                on_transition: o.on_transition,
+                cancelled_as: o.cancelled_as,
+                shed_below: o.shed_below,
+                degraded_failures: o.degraded_failures,
+                max_waiting: if clock.advances() { o.max_waiting } else { 0 },
+                max_in_flight: o.max_in_flight,
+                probe_rate: o.probe_rate,
+                max_queue_depth: o.max_queue_depth,
+                in_flight: sync::atomic::AtomicUsize::new(0),
+                max_wait: o.max_wait,
+                max_requests_per_tenant: o.max_requests_per_tenant,
+                parent: o.parent,
+                group: o.group,
+                phi_threshold: o.phi_threshold,
+                flap_window: o.flap_window,
+                flap_threshold: o.flap_threshold,
+                on_dampening: o.on_dampening,
+                idle_reset: o.idle_reset,
+                on_idle: o.on_idle,
+                on_callback_panic: o.on_callback_panic,
+                slow_call_duration: o.slow_call_duration,
+                slow_call_percentile: o.slow_call_percentile.map(SlowCallPercentile::clamped),
+                latencies: sync::Mutex::new(Latencies::new(now, o.slow_call_percentile.map(|slow| slow.window))),
+                on_success: o.on_success,
+                on_failure: o.on_failure,
+                history: o.history,
+                permits: sync::Condvar::new(),
+                maintenance_windows: sync::Mutex::new(Vec::new()),
+                listeners: Listeners::default(),
+                audit_log: AuditLog::default(),
+                paused: sync::atomic::AtomicBool::new(false),
+                clock,
+                shards: if o.counter_shards > 0 && !heartbeats {
+                    Some(Shards::new(o.counter_shards))
+                } else {
+                    None
+                },
+                buffers: o
+                    .buffering
+                    .filter(|buffering| buffering.max_outcomes > 0 && !heartbeats)
+                    .map(|buffering| Buffers::new(buffering, now)),
+                closed_generation: sync::atomic::AtomicU64::new(UNRECORDED),
+                inner: sync::Mutex::new(Inner {
+                    state: State::Closed,
+                    generation: 0,
+                    counts: Counts::new(),
+                    expires: None,
+                    fast: Counts::new(),
+                    fast_expires: None,
+                    waiting: collections::VecDeque::new(),
+                    next_ticket: 0,
+                    tenants: collections::HashMap::new(),
+                    phi: PhiAccrual::default(),
+                    flapping: Flapping::new(),
+                    last_call: None,
+                    history: collections::VecDeque::new(),
+                    totals: Totals::default(),
+                    forced: false,
+                    rng: o.rng.unwrap_or_else(|| Box::new(XorShift::default())),
+                    probes: ProbeBucket::new(now),
+                    profiles: Profiles::new(Profile {
+                        name: String::from(DEFAULT_PROFILE),
+                        max_requests: mr,
+                        success_threshold: o.success_threshold.unwrap_or(mr),
+                        interval: o.interval,
+                        timeout: if o.timeout > time::Duration::from_secs(0) {
+                            o.timeout
+                        } else {
+                            time::Duration::from_secs(60)
+                        },
+                        trip_policy: o.trip_policy.unwrap_or(TripPolicy::Custom(o.ready_to_trip)),
+                    }),
+                }),
+            };
+            {
+                let mut inner = poison::lock(&cb.inner);
+                cb.to_new_generation(&mut inner, now);
             }
+            cb
         }
 
-        fn prepare_state(&mut self) {
-            let mut state = self.state.lock().unwrap();
-            match *state {
-                State::Closed => {}
-                State::HalfOpen => {}
+        /// Like `new`, but rejects invalid options instead of adjusting them.
+        pub fn try_new(o: Options) -> Result<CircuitBreaker, errors::ConfigError> {
+            o.validate()?;
+            Ok(CircuitBreaker::new(o))
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        pub(crate) fn now(&self) -> time::Instant {
+            self.clock.now()
+        }
+
+        pub(crate) fn wall(&self) -> time::SystemTime {
+            self.clock.wall()
+        }
+
+        pub(crate) fn clock(&self) -> &dyn Clock {
+            &*self.clock
+        }
+
+        pub(crate) fn shared_clock(&self) -> sync::Arc<dyn Clock> {
+            self.clock.clone()
+        }
+
+        pub fn labels(&self) -> &collections::BTreeMap<String, String> {
+            &self.labels
+        }
+
+        /// The breaker this one reports its outcomes to. While the parent is Open, this breaker is forced Open too.
+        pub fn parent(&self) -> Option<&sync::Arc<CircuitBreaker>> {
+            self.parent.as_ref()
+        }
+
+        /// The failure budget this breaker shares with its siblings.
+        pub fn group(&self) -> Option<&sync::Arc<Group>> {
+            self.group.as_ref()
+        }
+
+        pub fn state(&self) -> State {
+            let mut inner = self.lock_inner();
+            self.current_state(&mut inner, self.now()).0
+        }
+
+        pub fn counts(&self) -> Counts {
+            self.lock_inner().counts
+        }
+
+        /// How long until an Open breaker lets probes through again; `None` unless Open, or if forced open.
+        pub fn retry_after(&self) -> Option<time::Duration> {
+            let mut inner = self.lock_inner();
+            let now = self.now();
+            match self.current_state(&mut inner, now).0 {
+                State::Open if !inner.forced => inner.expires.map(|expires| expires.saturating_duration_since(now)),
+                _ => None,
+            }
+        }
+
+        /// The state and counts, read together so they are consistent with each other.
+        pub fn snapshot(&self) -> Snapshot {
+            let mut inner = self.lock_inner();
+            let (state, generation) = self.current_state(&mut inner, self.now());
+            Snapshot {
+                name: self.name.clone(),
+                labels: self.labels.clone(),
+                state,
+                generation,
+                counts: inner.counts,
+                dampening: self.dampening_at(&inner),
+                profile: inner.profiles.active.name.clone(),
+            }
+        }
+
+        /// The counts of the current `fast_window`.
+        pub fn fast_counts(&self) -> Counts {
+            self.lock_inner().fast
+        }
+
+        /// Zeroes the statistics of the current window, leaving the state, generation and timers alone. While
+        /// HalfOpen the number of admitted probes is kept, so this does not let extra probes through.
+        pub fn clear_counts(&self) {
+            self.audit(AuditAction::ClearCounts, None);
+            let mut inner = self.lock_inner();
+            let requests = inner.counts.requests;
+            inner.counts.clear();
+            inner.fast.clear();
+            if inner.state == State::HalfOpen {
+                inner.counts.requests = requests;
+            }
+        }
+
+        /// Stops counting outcomes, e.g. during a failover drill, while still enforcing the current state.
+        /// Calls admitted while paused give their permit back without affecting the counts.
+        pub fn pause_recording(&self) {
+            self.audit(AuditAction::PauseRecording, None);
+            self.paused.store(true, sync::atomic::Ordering::SeqCst);
+        }
+
+        pub fn resume_recording(&self) {
+            self.audit(AuditAction::ResumeRecording, None);
+            self.paused.store(false, sync::atomic::Ordering::SeqCst);
+        }
+
+        pub fn is_recording_paused(&self) -> bool {
+            self.paused.load(sync::atomic::Ordering::SeqCst)
+        }
+
+        /// Holds the breaker Open, rejecting every call, until `reset`.
+        pub fn force_open(&self, reason: Option<&str>) {
+            self.force(State::Open, AuditAction::ForceOpen, reason);
+        }
+
+        /// Holds the breaker Closed, admitting every call and never tripping, until `reset`.
+        pub fn force_closed(&self, reason: Option<&str>) {
+            self.force(State::Closed, AuditAction::ForceClosed, reason);
+        }
+
+        fn force(&self, state: State, action: AuditAction, reason: Option<&str>) {
+            self.audit(action, reason);
+            let mut inner = self.lock_inner();
+            inner.forced = true;
+            self.set_state(&mut inner, state, self.now(), Reason::Forced);
+            self.permits.notify_all();
+        }
+
+        /// Releases a forced state and starts over Closed with fresh counts.
+        pub fn reset(&self, reason: Option<&str>) {
+            self.audit(AuditAction::Reset, reason);
+            let now = self.now();
+            let mut inner = self.lock_inner();
+            inner.forced = false;
+            if inner.state == State::Closed {
+                self.to_new_generation(&mut inner, now);
+            } else {
+                self.set_state(&mut inner, State::Closed, now, Reason::Reset);
+            }
+            self.permits.notify_all();
+        }
+
+        pub fn record_success(&self) {
+            self.record_outcome(Outcome::Success);
+        }
+
+        pub fn record_failure(&self) {
+            self.record_outcome(Outcome::Failure);
+        }
+
+        /// Records the outcome of a call made outside of `execute`. Ignored and rejected outcomes leave the
+        /// breaker untouched, and cancelled ones follow `Options::cancelled_as`.
+        pub fn record_outcome(&self, outcome: Outcome) {
+            if cfg!(feature = "noop") {
+                return;
+            }
+            let outcome = match (outcome, self.cancelled_as) {
+                (Outcome::Ignored, _) | (Outcome::Rejected, _) | (Outcome::Cancelled, CancelledAs::Ignored) => return,
+                (Outcome::Cancelled, CancelledAs::Failure) => Outcome::Failure,
+                (Outcome::Cancelled, CancelledAs::Success) => Outcome::Success,
+                (outcome, _) => outcome,
+            };
+            if self.is_recording_paused() {
+                return;
+            }
+            if maintenance::active(&poison::lock(&self.maintenance_windows), self.wall()).is_some() {
+                return;
+            }
+            if let Some(ref parent) = self.parent {
+                parent.record_outcome(outcome);
+            }
+            let now = self.now();
+            let mut inner = poison::lock(&self.inner);
+            let (state, _) = self.current_state(&mut inner, now);
+            inner.last_call = Some(now);
+            inner.requested();
+            self.apply(&mut inner, state, now, outcome);
+            self.permits.notify_all();
+        }
+
+        fn current_state(&self, inner: &mut Inner, now: time::Instant) -> (State, u64) {
+            inner.profiles.apply_schedule(&*self.clock);
+            if inner.forced {
+                return (inner.state, inner.generation);
+            }
+            if let Some(ref buffers) = self.buffers {
+                buffers.take_due(now, |buffered| self.hand_over(inner, buffered, now));
+            }
+            match inner.state {
+                State::Closed => {
+                    if inner.expires.is_some_and(|expires| expires <= now) {
+                        self.to_new_generation(inner, now);
+                    } else if inner.fast_expires.is_some_and(|expires| expires <= now) {
+                        inner.fast.clear();
+                        inner.fast_expires = Some(now + self.fast_window);
+                    }
+                    let idle = self.idle_reset > time::Duration::from_secs(0)
+                        && inner
+                            .last_call
+                            .is_some_and(|last_call| now.duration_since(last_call) >= self.idle_reset);
+                    if idle {
+                        inner.last_call = None;
+                        self.to_new_generation(inner, now);
+                        self.guarded(|| (self.on_idle)(self.name.clone()));
+                    }
+                    if self.flap_window > time::Duration::from_secs(0) && inner.flapping.settle(now, self.flap_window) {
+                        let dampening = self.dampening_at(inner);
+                        self.guarded(|| (self.on_dampening)(self.name.clone(), dampening));
+                    }
+                }
                 State::Open => {
-                    if self.expires
-                           .unwrap_or(time::Instant::now())
-                           .duration_since(time::Instant::now()) > time::Duration::from_secs(0) {
-                        *state = State::HalfOpen;
+                    if inner.expires.is_some_and(|expires| expires <= now) {
+                        self.set_state(inner, State::HalfOpen, now, Reason::OpenTimeoutElapsed);
+                    }
+                }
+                State::HalfOpen => {
+                    if inner.expires.is_some_and(|expires| expires <= now) {
+                        self.set_state(inner, State::Open, now, Reason::HalfOpenExpired);
                     }
                 }
             };
+            (inner.state, inner.generation)
         }
 
-        fn succeeded(&self) {
-            unimplemented!();
+        fn succeeded(&self, inner: &mut Inner, state: State, now: time::Instant, slow: bool) {
+            if self.phi_threshold.is_some() {
+                inner.phi.heartbeat(now);
+            }
+            if state != State::Open {
+                inner.totals.successes = inner.totals.successes.saturating_add(1);
+            }
+            match state {
+                State::Closed => {
+                    inner.counts.succeeded(slow);
+                    inner.fast.succeeded(slow);
+                }
+                State::HalfOpen => {
+                    inner.counts.succeeded(slow);
+                    if inner.counts.consecutive_successes >= u64::from(self.dampening_at(inner).success_threshold) {
+                        self.set_state(inner, State::Closed, now, Reason::ProbesSucceeded);
+                    }
+                }
+                State::Open => {}
+            }
         }
 
-        fn failed(&mut self) {
-            {
-                let state = self.state.lock().unwrap();
-                match *state {
-                    State::Closed => {
-                        return;
+        fn failed(&self, inner: &mut Inner, state: State, now: time::Instant, weight: f64) {
+            if let (Some(group), false) = (self.group.as_ref(), state == State::Open) {
+                group.failed();
+            }
+            if state != State::Open {
+                inner.totals.failures = inner.totals.failures.saturating_add(1);
+            }
+            match state {
+                State::Closed => {
+                    inner.counts.failed(weight);
+                    inner.fast.failed(weight);
+                    let suspected = self.phi_threshold.is_some_and(|threshold| inner.phi.phi(now) > threshold);
+                    let spiked = self.fast_window > time::Duration::from_secs(0) && (self.fast_ready_to_trip)(inner.fast);
+                    let tripped = inner.profiles.active.trip_policy.should_trip(inner.counts) || spiked || suspected;
+                    if tripped && !inner.forced {
+                        self.set_state(inner, State::Open, now, Reason::Tripped);
+                    }
+                }
+                State::HalfOpen => self.set_state(inner, State::Open, now, Reason::ProbeFailed),
+                State::Open => {}
+            }
+        }
+
+        fn set_state(&self, inner: &mut Inner, new_state: State, now: time::Instant, reason: Reason) {
+            let old_state = inner.state;
+            if old_state == new_state {
+                return;
+            }
+            inner.state = new_state;
+            let dampened = new_state == State::Open
+                && self.flap_window > time::Duration::from_secs(0)
+                && inner.flapping.opened(now, self.flap_window, self.flap_threshold);
+            self.to_new_generation(inner, now);
+            self.guarded(|| (self.on_state_change)(self.name.clone(), old_state, new_state));
+            self.guarded(|| (self.on_transition)(self.name.clone(), old_state, new_state, reason));
+            if self.history > 0 || !self.listeners.is_empty() {
+                let transition = Transition {
+                    name: self.name.clone(),
+                    labels: self.labels.clone(),
+                    from: old_state,
+                    to: new_state,
+                    reason,
+                    generation: inner.generation,
+                };
+                self.listeners.notify(self, &transition);
+                if self.history > 0 {
+                    if inner.history.len() >= self.history {
+                        inner.history.pop_front();
                     }
-                    State::HalfOpen => {}
-                    State::Open => {
-                        return;
+                    inner.history.push_back(transition);
+                }
+            }
+            if dampened {
+                let dampening = self.dampening_at(inner);
+                self.guarded(|| (self.on_dampening)(self.name.clone(), dampening));
+            }
+        }
+
+        fn dampening_at(&self, inner: &Inner) -> Dampening {
+            let level = inner.flapping.level;
+            Dampening {
+                level,
+                timeout: inner.profiles.active.timeout * 2u32.pow(level),
+                success_threshold: inner.profiles.active.success_threshold.saturating_mul(level + 1),
+                max_requests: inner.profiles.active.max_requests.saturating_mul(level + 1),
+            }
+        }
+
+        pub fn dampening(&self) -> Dampening {
+            self.dampening_at(&self.lock_inner())
+        }
+
+        /// Runs a user callback, reporting a panic to `on_callback_panic` instead of letting it unwind through
+        /// the breaker while its lock is held.
+        pub(crate) fn guarded<F: FnOnce()>(&self, callback: F) {
+            if let Err(payload) = panic::catch_unwind(panic::AssertUnwindSafe(callback)) {
+                let message = match payload.downcast::<String>() {
+                    Ok(message) => *message,
+                    Err(payload) => payload
+                        .downcast_ref::<&str>()
+                        .map_or_else(|| String::from("callback panicked"), |message| String::from(*message)),
+                };
+                let (on_callback_panic, name) = (self.on_callback_panic, self.name.clone());
+                let _ = panic::catch_unwind(move || on_callback_panic(name, message));
+            }
+        }
+
+        /// Takes the lock, first adding up any sharded successes and handing over buffered outcomes. A buffer
+        /// its thread is busy with is left for that thread to hand over.
+        pub(crate) fn lock_inner(&self) -> sync::MutexGuard<'_, Inner> {
+            let mut inner = poison::lock(&self.inner);
+            self.fold_shards(&mut inner);
+            if let Some(ref buffers) = self.buffers {
+                let now = self.now();
+                for buffered in buffers.all() {
+                    if let Some(mut buffered) = poison::try_lock(buffered) {
+                        self.hand_over(&mut inner, &mut buffered, now);
                     }
                 }
             }
-            self.set_state(State::Open);
+            inner
         }
 
-        fn set_state(&mut self, new_state: State) {
-            let mut state = self.state.lock().unwrap();
-            let old_state = *state;
+        /// Applies buffered outcomes in order, dropping those from a window that has since ended.
+        fn hand_over(&self, inner: &mut Inner, buffered: &mut Buffered, now: time::Instant) {
+            for (generation, outcome) in buffered.drain() {
+                if generation == inner.generation && inner.state == State::Closed {
+                    self.apply(inner, State::Closed, now, outcome);
+                }
+            }
         }
 
-        pub fn execute<T, E>(&mut self, task: fn() -> Result<T, E>) -> Result<Result<T, E>, errors::CircuitBreakerError> {
-            self.prepare_state();
-            {
-                let state = self.state.lock().unwrap();
-                match *state {
-                    State::Closed => {}
-                    State::HalfOpen => {
-                        if self.counts.requests > self.max_requests {
+        fn buffer(&self, buffers: &Buffers, before: u64, outcome: Outcome) {
+            let now = self.now();
+            let mut buffered = poison::lock(buffers.local());
+            if buffers.push(&mut buffered, before, outcome, now) {
+                let mut inner = self.lock_inner();
+                self.current_state(&mut inner, now);
+                self.hand_over(&mut inner, &mut buffered, now);
+                self.permits.notify_all();
+            }
+        }
+
+        fn fold_shards(&self, inner: &mut Inner) {
+            let (successes, slow) = match self.shards {
+                Some(ref shards) => shards.drain(),
+                None => return,
+            };
+            inner.totals.successes = inner.totals.successes.saturating_add(successes);
+            if inner.state == State::Closed {
+                inner.counts.absorb(successes, slow);
+                inner.fast.absorb(successes, slow);
+            }
+        }
+
+        fn to_new_generation(&self, inner: &mut Inner, now: time::Instant) {
+            self.fold_shards(inner);
+            inner.generation += 1;
+            inner.counts.clear();
+            inner.fast.clear();
+            inner.tenants.clear();
+            inner.probes = ProbeBucket::new(now);
+            inner.fast_expires = match inner.state {
+                State::Closed if self.fast_window > time::Duration::from_secs(0) => Some(now + self.fast_window),
+                _ => None,
+            };
+            inner.expires = match inner.state {
+                State::Closed if inner.profiles.active.interval > time::Duration::from_secs(0) => {
+                    Some(now + inner.profiles.active.interval)
+                }
+                State::HalfOpen if self.max_half_open_duration > time::Duration::from_secs(0) => {
+                    Some(now + self.max_half_open_duration)
+                }
+                // However short the (dampened) timeout, the downstream gets at least `min_open_duration` of quiet.
+                State::Open => {
+                    let mut timeout = self.dampening_at(inner).timeout;
+                    if self.timeout_jitter > 0.0 {
+                        timeout = timeout.mul_f64(1.0 + self.timeout_jitter * inner.rng.next_f64());
+                    }
+                    Some(now + cmp::max(timeout, self.min_open_duration))
+                }
+                _ => None,
+            };
+            let closed_generation = if inner.state == State::Closed {
+                inner.generation
+            } else {
+                UNRECORDED
+            };
+            self.closed_generation
+                .store(closed_generation, sync::atomic::Ordering::Release);
+        }
+
+        pub(crate) fn before_request(&self) -> Result<u64, errors::CircuitBreakerError> {
+            self.before_request_with(Priority::Normal, None)
+        }
+
+        fn degraded(&self, inner: &Inner, state: State) -> bool {
+            match state {
+                State::Closed => self
+                    .degraded_failures
+                    .is_some_and(|threshold| inner.counts.consecutive_failures >= u64::from(threshold)),
+                State::HalfOpen => true,
+                State::Open => false,
+            }
+        }
+
+        pub(crate) fn before_request_in(&self, context: &Context) -> Result<u64, errors::CircuitBreakerError> {
+            self.admit_reporting(Priority::Normal, None, context)
+        }
+
+        pub(crate) fn before_request_with(
+            &self,
+            priority: Priority,
+            tenant: Option<&str>,
+        ) -> Result<u64, errors::CircuitBreakerError> {
+            self.admit_reporting(priority, tenant, &Context::default())
+        }
+
+        /// Admits a call, counting and reporting it as rejected if it is turned away. With the `noop` feature
+        /// every call is admitted unrecorded, so nothing trips the breaker; manual transitions such as
+        /// `force_open` still change its state, but no longer affect admission.
+        fn admit_reporting(
+            &self,
+            priority: Priority,
+            tenant: Option<&str>,
+            context: &Context,
+        ) -> Result<u64, errors::CircuitBreakerError> {
+            if cfg!(feature = "noop") {
+                return Ok(UNRECORDED);
+            }
+            let mut inner = poison::lock(&self.inner);
+            let admitted = match self.overloaded(&inner) {
+                Some(err) => Err(err),
+                None => self.admit(&mut inner, priority, tenant, false),
+            };
+            let result = match admitted {
+                Err(ref err) if err.kind == errors::CircuitBreakerErrorKind::TooManyRequestsError && self.max_waiting > 0 => {
+                    let (waited, result) = self.wait_for_permit(inner, priority, tenant);
+                    inner = waited;
+                    result
+                }
+                result => result,
+            };
+            match result {
+                Err(ref err) if err.kind == errors::CircuitBreakerErrorKind::StateOpenError => {
+                    inner.counts.rejected = inner.counts.rejected.saturating_add(1);
+                    inner.totals.rejected = inner.totals.rejected.saturating_add(1);
+                }
+                Err(ref err) if err.kind == errors::CircuitBreakerErrorKind::TooManyRequestsError => {
+                    inner.counts.not_permitted = inner.counts.not_permitted.saturating_add(1);
+                    inner.totals.not_permitted = inner.totals.not_permitted.saturating_add(1);
+                }
+                _ => {}
+            }
+            if result.is_ok() {
+                self.in_flight.fetch_add(1, sync::atomic::Ordering::Relaxed);
+            }
+            if result.is_err() {
+                let generation = inner.generation;
+                drop(inner);
+                self.report_call(generation, Outcome::Rejected, time::Duration::from_secs(0), context);
+            }
+            result
+        }
+
+        /// Load-driven shedding, checked under the lock so that admissions cannot overshoot `max_in_flight`.
+        fn overloaded(&self, inner: &Inner) -> Option<errors::CircuitBreakerError> {
+            let in_flight = self.in_flight.load(sync::atomic::Ordering::Relaxed);
+            let busy = self.max_in_flight.is_some_and(|max| in_flight >= max as usize)
+                || self.max_queue_depth.is_some_and(|depth| inner.waiting.len() >= depth);
+            if busy {
+                Some(errors::CircuitBreakerError {
+                    kind: errors::CircuitBreakerErrorKind::OverloadedError,
+                    message: "The call was shed because too many calls are in flight or waiting".into(),
+                })
+            } else {
+                None
+            }
+        }
+
+        /// Admitted calls currently running.
+        pub fn in_flight(&self) -> usize {
+            self.in_flight.load(sync::atomic::Ordering::Relaxed)
+        }
+
+        fn finished(&self) {
+            let _ = self
+                .in_flight
+                .fetch_update(sync::atomic::Ordering::Relaxed, sync::atomic::Ordering::Relaxed, |n| {
+                    n.checked_sub(1)
+                });
+        }
+
+        fn admit(
+            &self,
+            inner: &mut Inner,
+            priority: Priority,
+            tenant: Option<&str>,
+            queued: bool,
+        ) -> Result<u64, errors::CircuitBreakerError> {
+            match maintenance::active(&poison::lock(&self.maintenance_windows), self.wall()) {
+                Some(Maintenance::ForceOpen) => {
+                    return Err(errors::CircuitBreakerError {
+                        kind: errors::CircuitBreakerErrorKind::MaintenanceError,
+                        message: "The CircuitBreaker is open for scheduled maintenance".into(),
+                    });
+                }
+                Some(Maintenance::Shadow) => return Ok(UNRECORDED),
+                None => {}
+            }
+            let now = self.now();
+            // A forced state is left alone by the parent and the group alike.
+            if !inner.forced {
+                if self.parent.as_ref().is_some_and(|parent| parent.state() == State::Open) {
+                    self.set_state(inner, State::Open, now, Reason::ParentOpen);
+                } else if self.group.as_ref().is_some_and(|group| group.exhausted()) {
+                    self.set_state(inner, State::Open, now, Reason::GroupExhausted);
+                }
+            }
+            let (state, generation) = self.current_state(inner, now);
+            if priority < self.shed_below && self.degraded(inner, state) {
+                return Err(errors::CircuitBreakerError {
+                    kind: errors::CircuitBreakerErrorKind::ShedError,
+                    message: "The call was shed because of its low priority while the CircuitBreaker is degraded".into(),
+                });
+            }
+            match state {
+                State::Closed => {}
+                State::HalfOpen => {
+                    // Calls already waiting for a permit are served first.
+                    if !queued && !inner.waiting.is_empty() {
+                        return Err(too_many_requests());
+                    }
+                    let permitted = match self.probe_rate {
+                        Some(rate) => inner.probes.take(rate, now),
+                        None => inner.counts.requests < u64::from(self.dampening_at(inner).max_requests),
+                    };
+                    if !permitted {
+                        return Err(too_many_requests());
+                    }
+                    if let (Some(tenant), Some(limit)) = (tenant, self.max_requests_per_tenant) {
+                        let probes = inner.tenants.entry(tenant.to_string()).or_insert(0);
+                        if *probes >= limit {
                             return Err(errors::CircuitBreakerError {
-                                kind: errors::CircuitBreakerErrorKind::TooManyRequestsError,
-                                message: "Maximum requests limit has reached while the CircuitBreaker is HalfOpen".into(),
+                                kind: errors::CircuitBreakerErrorKind::TenantQuotaError,
+                                message: format!(
+                                    "Tenant {} has used up its requests while the CircuitBreaker is HalfOpen",
+                                    tenant
+                                )
+                                .into(),
                             });
                         }
+                        *probes += 1;
                     }
-                    State::Open => {
-                        return Err(errors::CircuitBreakerError {
-                            kind: errors::CircuitBreakerErrorKind::StateOpenError,
-                            message: "The CircuitBreaker is open".into(),
-                        });
+                }
+                State::Open => {
+                    return Err(errors::CircuitBreakerError {
+                        kind: errors::CircuitBreakerErrorKind::StateOpenError,
+                        message: "The CircuitBreaker is open".into(),
+                    });
+                }
+            };
+            inner.last_call = Some(now);
+            inner.requested();
+            Ok(generation)
+        }
+
+        /// Blocks the calling thread in a FIFO queue of at most `max_waiting` calls until a permit frees up,
+        /// the circuit opens, or `max_wait` passes.
+        fn wait_for_permit<'a>(
+            &'a self,
+            mut inner: sync::MutexGuard<'a, Inner>,
+            priority: Priority,
+            tenant: Option<&str>,
+        ) -> (sync::MutexGuard<'a, Inner>, Result<u64, errors::CircuitBreakerError>) {
+            if inner.waiting.len() >= self.max_waiting {
+                return (inner, Err(too_many_requests()));
+            }
+            inner.next_ticket += 1;
+            let ticket = inner.next_ticket;
+            inner.waiting.push_back(ticket);
+            // The wait is on a Condvar, so it is timed in real time; `new` turns waiting off for clocks that
+            // do not advance by themselves.
+            let deadline = time::Instant::now() + self.max_wait;
+            loop {
+                let now = time::Instant::now();
+                if inner.waiting.front() == Some(&ticket) {
+                    match self.admit(&mut inner, priority, tenant, true) {
+                        Err(ref err) if err.kind == errors::CircuitBreakerErrorKind::TooManyRequestsError && now < deadline => {
+                        }
+                        result => {
+                            inner.waiting.pop_front();
+                            self.permits.notify_all();
+                            return (inner, result);
+                        }
                     }
-                };
+                } else if now >= deadline {
+                    inner.waiting.retain(|waiting| *waiting != ticket);
+                    self.permits.notify_all();
+                    return (inner, Err(too_many_requests()));
+                }
+                inner = poison::wait_timeout(&self.permits, inner, deadline - now);
             }
-            self.counts.requested();
-            let task_result = task();
-            match task_result {
-                Ok(res) => {
-                    self.succeeded();
-                    return Ok(Ok(res));
+        }
+
+        /// The one path by which the outcome of an admitted call reaches the breaker.
+        pub(crate) fn after_outcome(&self, before: u64, outcome: Outcome) {
+            if cfg!(feature = "noop") {
+                return;
+            }
+            self.finished();
+            self.settle(before, outcome);
+        }
+
+        fn settle(&self, before: u64, outcome: Outcome) {
+            match outcome {
+                Outcome::Ignored | Outcome::Rejected => return self.give_back(before),
+                Outcome::Cancelled => return self.after_cancelled(before),
+                _ => {}
+            }
+            if before == UNRECORDED {
+                return;
+            }
+            if self.is_recording_paused() {
+                self.give_back(before);
+                return;
+            }
+            if let Some(ref parent) = self.parent {
+                parent.record_outcome(outcome);
+            }
+            if let Some(ref shards) = self.shards {
+                if matches!(outcome, Outcome::Success | Outcome::Slow)
+                    && self.closed_generation.load(sync::atomic::Ordering::Acquire) == before
+                {
+                    shards.add(outcome == Outcome::Slow);
+                    return;
                 }
-                Err(err) => {
-                    self.failed();
-                    return Ok(Err(err));
+            }
+            if let Some(ref buffers) = self.buffers {
+                if self.closed_generation.load(sync::atomic::Ordering::Acquire) == before {
+                    return self.buffer(buffers, before, outcome);
                 }
             }
+            let now = self.now();
+            let mut inner = self.lock_inner();
+            let (state, generation) = self.current_state(&mut inner, now);
+            if generation != before {
+                return;
+            }
+            self.apply(&mut inner, state, now, outcome);
+            self.permits.notify_all();
+        }
+
+        fn apply(&self, inner: &mut Inner, state: State, now: time::Instant, outcome: Outcome) {
+            match outcome {
+                Outcome::Success => self.succeeded(inner, state, now, false),
+                Outcome::Slow => self.succeeded(inner, state, now, true),
+                Outcome::Failure => self.failed(inner, state, now, 1.0),
+                Outcome::WeightedFailure(weight) => self.failed(inner, state, now, weight),
+                Outcome::Ignored | Outcome::Rejected | Outcome::Cancelled => {}
+            }
+        }
+
+        /// Judges a completed call's duration against the slow-call settings, then adds it to the latency history.
+        pub(crate) fn is_slow(&self, duration: time::Duration) -> bool {
+            let now = self.now();
+            let mut latencies = poison::lock(&self.latencies);
+            let slow = (self.slow_call_duration > time::Duration::from_secs(0) && duration >= self.slow_call_duration)
+                || self
+                    .slow_call_percentile
+                    .is_some_and(|slow| latencies.is_slow(now, duration, slow));
+            latencies.record(now, duration);
+            slow
+        }
+
+        /// Gives back a permit that was admitted but never used, without recording an outcome.
+        pub(crate) fn release(&self, before: u64) {
+            if cfg!(feature = "noop") {
+                return;
+            }
+            self.finished();
+            self.give_back(before);
+        }
+
+        fn give_back(&self, before: u64) {
+            let mut inner = poison::lock(&self.inner);
+            if inner.generation == before {
+                inner.counts.requests = inner.counts.requests.saturating_sub(1);
+                inner.fast.requests = inner.fast.requests.saturating_sub(1);
+            }
+            self.permits.notify_all();
+        }
+
+        fn after_cancelled(&self, before: u64) {
+            match self.cancelled_as {
+                CancelledAs::Ignored => self.give_back(before),
+                CancelledAs::Failure => self.settle(before, Outcome::Failure),
+                CancelledAs::Success => self.settle(before, Outcome::Success),
+            }
+        }
+
+        /// With the `noop` feature, just calls `task`. A task that panics is recorded as cancelled.
+        pub fn execute<T, E>(&self, task: fn() -> Result<T, E>) -> Result<Result<T, E>, errors::CircuitBreakerError> {
+            if cfg!(feature = "noop") {
+                return Ok(task());
+            }
+            let permit = Permit::acquire(self)?;
+            let task_result = task();
+            permit.record(Outcome::from_success(task_result.is_ok()));
+            Ok(task_result)
+        }
+
+        pub(crate) fn report_call(&self, generation: u64, outcome: Outcome, duration: time::Duration, context: &Context) {
+            match outcome {
+                Outcome::Success | Outcome::Slow => self.guarded(|| (self.on_success)(&self.name, duration, context)),
+                Outcome::Failure | Outcome::WeightedFailure(_) => {
+                    self.guarded(|| (self.on_failure)(&self.name, duration, context))
+                }
+                Outcome::Ignored | Outcome::Rejected | Outcome::Cancelled => {}
+            }
+            if !self.listeners.is_empty() {
+                self.listeners.notify_call(
+                    self,
+                    &CallEvent {
+                        name: self.name.clone(),
+                        generation,
+                        outcome,
+                        duration,
+                        context: context.clone(),
+                    },
+                );
+            }
         }
     }
 }
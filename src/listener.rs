@@ -0,0 +1,104 @@
+use audit::AuditEntry;
+use context::Context;
+use interpact::{CircuitBreaker, Transition};
+use outcome::Outcome;
+use poison;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time;
+use watchdog::Escalation;
+
+/// Observes the state changes of a breaker. Listeners run on the thread that caused the transition, while the
+/// breaker is locked, so they must be quick and must not call back into the same breaker.
+pub trait Listener: Send + Sync {
+    fn on_transition(&self, transition: &Transition);
+
+    /// Called after every call run through `execute` and its variants. Ignored unless overridden.
+    fn on_call(&self, _call: &CallEvent) {}
+
+    /// Called after every manual action recorded in the audit log, such as `force_open` or `switch_profile`.
+    /// Ignored unless overridden.
+    fn on_audit(&self, _name: &str, _entry: &AuditEntry) {}
+
+    /// Called when a `Watchdog` escalates this breaker for staying unhealthy. Ignored unless overridden.
+    fn on_escalation(&self, _escalation: &Escalation) {}
+}
+
+/// A completed call, with how it was classified and how long it took.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CallEvent {
+    pub name: String,
+    pub generation: u64,
+    pub outcome: Outcome,
+    pub duration: time::Duration,
+    pub context: Context,
+}
+
+impl<F> Listener for F
+where
+    F: Fn(&Transition) + Send + Sync,
+{
+    fn on_transition(&self, transition: &Transition) {
+        self(transition)
+    }
+}
+
+/// Identifies a listener added with `add_listener`, for removing it again.
+#[must_use = "a listener can only be removed through its handle"]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerHandle {
+    id: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct Listeners {
+    next_id: AtomicU64,
+    listeners: RwLock<Vec<(u64, Arc<dyn Listener>)>>,
+}
+
+impl Listeners {
+    pub(crate) fn is_empty(&self) -> bool {
+        poison::read(&self.listeners).is_empty()
+    }
+
+    pub(crate) fn notify(&self, cb: &CircuitBreaker, transition: &Transition) {
+        for (_, listener) in poison::read(&self.listeners).iter() {
+            cb.guarded(|| listener.on_transition(transition));
+        }
+    }
+
+    pub(crate) fn notify_call(&self, cb: &CircuitBreaker, call: &CallEvent) {
+        for (_, listener) in poison::read(&self.listeners).iter() {
+            cb.guarded(|| listener.on_call(call));
+        }
+    }
+
+    pub(crate) fn notify_audit(&self, cb: &CircuitBreaker, entry: &AuditEntry) {
+        for (_, listener) in poison::read(&self.listeners).iter() {
+            cb.guarded(|| listener.on_audit(cb.name(), entry));
+        }
+    }
+
+    pub(crate) fn notify_escalation(&self, cb: &CircuitBreaker, escalation: &Escalation) {
+        for (_, listener) in poison::read(&self.listeners).iter() {
+            cb.guarded(|| listener.on_escalation(escalation));
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn add_listener<L: Listener + 'static>(&self, listener: L) -> ListenerHandle {
+        let id = self.listeners.next_id.fetch_add(1, Ordering::Relaxed);
+        poison::write(&self.listeners.listeners).push((id, Arc::new(listener)));
+        ListenerHandle { id }
+    }
+
+    /// Detaches a listener; returns false if it had already been removed.
+    pub fn remove_listener(&self, handle: ListenerHandle) -> bool {
+        let mut listeners = poison::write(&self.listeners.listeners);
+        let before = listeners.len();
+        listeners.retain(|&(id, _)| id != handle.id);
+        listeners.len() != before
+    }
+}
@@ -0,0 +1,223 @@
+//! Structured log records for transitions, calls and manual actions, for setups that log rather than trace.
+//! `Logged` hands each record as a `key=value` line to a function, which is where it meets the logging
+//! library in use:
+//!
+//! ```ignore
+//! cb.add_listener(Logged::new(|level, message| match level {
+//!     Level::Warn => log::warn!(target: "interpact", "{}", message),
+//!     Level::Info => log::info!(target: "interpact", "{}", message),
+//!     Level::Debug => log::debug!(target: "interpact", "{}", message),
+//! }));
+//! ```
+//!
+//! `Structured` hands over the fields instead, for loggers with key-value support such as slog:
+//!
+//! ```ignore
+//! cb.add_listener(Structured::new(move |record: &Record| {
+//!     let fields = record.fields.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>();
+//!     slog::info!(logger, "{}", record.message; "fields" => fields.join(" "))
+//! }));
+//! ```
+
+use audit::{AuditAction, AuditEntry};
+use interpact::{State, Transition};
+use listener::{CallEvent, Listener};
+use outcome::Outcome;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Warn,
+    Info,
+    Debug,
+}
+
+/// A log message with its fields. Displayed in logfmt style, as the message followed by `key=value` pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub level: Level,
+    pub message: &'static str,
+    pub fields: Vec<(String, String)>,
+}
+
+impl Record {
+    fn new(level: Level, message: &'static str, breaker: &str) -> Record {
+        Record {
+            level,
+            message,
+            fields: vec![("breaker".into(), breaker.into())],
+        }
+    }
+
+    fn field<V: fmt::Display>(mut self, name: &str, value: V) -> Record {
+        self.fields.push((name.into(), value.to_string()));
+        self
+    }
+
+    fn prefixed(mut self, prefix: &str, values: &BTreeMap<String, String>) -> Record {
+        for (name, value) in values {
+            self.fields.push((format!("{}.{}", prefix, name), value.clone()));
+        }
+        self
+    }
+}
+
+/// A value written in logfmt style: bare if it is a plain word, quoted and escaped otherwise.
+struct Value<'a>(&'a str);
+
+impl<'a> fmt::Display for Value<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bare = !self.0.is_empty()
+            && self
+                .0
+                .chars()
+                .all(|c| !c.is_whitespace() && !c.is_control() && c != '=' && c != '"');
+        if bare {
+            return f.write_str(self.0);
+        }
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)?;
+        for (name, value) in &self.fields {
+            write!(f, " {}={}", name, Value(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// A transition into Open is a warning; every other transition is informational.
+pub fn transition(transition: &Transition) -> Record {
+    let level = if transition.to == State::Open {
+        Level::Warn
+    } else {
+        Level::Info
+    };
+    Record::new(level, "circuit breaker changed state", &transition.name)
+        .field("from", transition.from)
+        .field("to", transition.to)
+        .field("reason", format!("{:?}", transition.reason))
+        .field("generation", transition.generation)
+        .prefixed("label", &transition.labels)
+}
+
+/// Rejected and failed calls are informational; every other outcome is debug output.
+pub fn call(call: &CallEvent) -> Record {
+    let (level, outcome) = match call.outcome {
+        Outcome::Success => (Level::Debug, "success"),
+        Outcome::Slow => (Level::Debug, "slow"),
+        Outcome::Failure | Outcome::WeightedFailure(_) => (Level::Info, "failure"),
+        Outcome::Ignored => (Level::Debug, "ignored"),
+        Outcome::Rejected => (Level::Info, "rejected"),
+        Outcome::Cancelled => (Level::Debug, "cancelled"),
+    };
+    let message = if call.outcome == Outcome::Rejected {
+        "circuit breaker rejected a call"
+    } else {
+        "circuit breaker recorded a call"
+    };
+    let mut record = Record::new(level, message, &call.name)
+        .field("generation", call.generation)
+        .field("outcome", outcome);
+    if let Outcome::WeightedFailure(weight) = call.outcome {
+        record = record.field("weight", weight);
+    }
+    if call.outcome != Outcome::Rejected {
+        record = record.field("duration_ms", call.duration.as_secs_f64() * 1000.0);
+    }
+    record.prefixed("context", call.context.entries())
+}
+
+pub fn audit(name: &str, entry: &AuditEntry) -> Record {
+    let (action, profile) = match entry.action {
+        AuditAction::ForceOpen => ("force_open", None),
+        AuditAction::ForceClosed => ("force_closed", None),
+        AuditAction::Reset => ("reset", None),
+        AuditAction::ClearCounts => ("clear_counts", None),
+        AuditAction::PauseRecording => ("pause_recording", None),
+        AuditAction::ResumeRecording => ("resume_recording", None),
+        AuditAction::AddProfile(ref profile) => ("add_profile", Some(profile)),
+        AuditAction::SwitchProfile(ref profile) => ("switch_profile", Some(profile)),
+        AuditAction::ScheduleProfile(ref profile) => ("schedule_profile", Some(profile)),
+        AuditAction::AddMaintenanceWindow => ("add_maintenance_window", None),
+        AuditAction::ClearMaintenanceWindows => ("clear_maintenance_windows", None),
+    };
+    let mut record = Record::new(Level::Info, "circuit breaker configuration changed", name).field("action", action);
+    if let Some(profile) = profile {
+        record = record.field("profile", profile);
+    }
+    if let Some(ref reason) = entry.reason {
+        record = record.field("reason", reason);
+    }
+    record
+}
+
+/// A listener that logs transitions, rejections and manual actions as `key=value` lines through `log`. Other
+/// call outcomes are too frequent for a plain log and are left out.
+pub struct Logged {
+    log: fn(level: Level, message: &str),
+}
+
+impl Logged {
+    pub fn new(log: fn(level: Level, message: &str)) -> Logged {
+        Logged { log }
+    }
+}
+
+impl Listener for Logged {
+    fn on_transition(&self, transition: &Transition) {
+        let record = self::transition(transition);
+        (self.log)(record.level, &record.to_string());
+    }
+
+    fn on_call(&self, call: &CallEvent) {
+        if call.outcome == Outcome::Rejected {
+            let record = self::call(call);
+            (self.log)(record.level, &record.to_string());
+        }
+    }
+
+    fn on_audit(&self, name: &str, entry: &AuditEntry) {
+        let record = audit(name, entry);
+        (self.log)(record.level, &record.to_string());
+    }
+}
+
+/// A listener that hands every transition, call and manual action to `log` as a `Record`, for loggers that
+/// keep fields apart from the message. Unlike `Logged` it takes a closure, so it can hold on to a logger.
+pub struct Structured<F> {
+    log: F,
+}
+
+impl<F: Fn(&Record) + Send + Sync> Structured<F> {
+    pub fn new(log: F) -> Structured<F> {
+        Structured { log }
+    }
+}
+
+impl<F: Fn(&Record) + Send + Sync> Listener for Structured<F> {
+    fn on_transition(&self, transition: &Transition) {
+        (self.log)(&self::transition(transition));
+    }
+
+    fn on_call(&self, call: &CallEvent) {
+        (self.log)(&self::call(call));
+    }
+
+    fn on_audit(&self, name: &str, entry: &AuditEntry) {
+        (self.log)(&audit(name, entry));
+    }
+}
@@ -0,0 +1,57 @@
+use audit::AuditAction;
+use interpact::CircuitBreaker;
+use poison;
+use std::time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Maintenance {
+    /// Reject every call without touching the breaker's state or counts.
+    ForceOpen,
+    /// Let every call through and record none of the outcomes.
+    Shadow,
+}
+
+/// A planned downstream maintenance window, optionally repeating every `every` after `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    pub start: time::SystemTime,
+    pub duration: time::Duration,
+    pub every: Option<time::Duration>,
+    pub mode: Maintenance,
+}
+
+impl MaintenanceWindow {
+    pub fn is_active(&self, now: time::SystemTime) -> bool {
+        let since_start = match now.duration_since(self.start) {
+            Ok(since_start) => since_start,
+            Err(_) => return false,
+        };
+        let offset = match self.every {
+            Some(every) if every > time::Duration::from_secs(0) => {
+                time::Duration::from_nanos((since_start.as_nanos() % every.as_nanos()) as u64)
+            }
+            _ => since_start,
+        };
+        offset < self.duration
+    }
+}
+
+pub(crate) fn active(windows: &[MaintenanceWindow], now: time::SystemTime) -> Option<Maintenance> {
+    windows.iter().find(|window| window.is_active(now)).map(|window| window.mode)
+}
+
+impl CircuitBreaker {
+    pub fn add_maintenance_window(&self, window: MaintenanceWindow) {
+        self.audit(AuditAction::AddMaintenanceWindow, None);
+        poison::lock(&self.maintenance_windows).push(window);
+    }
+
+    pub fn clear_maintenance_windows(&self) {
+        self.audit(AuditAction::ClearMaintenanceWindows, None);
+        poison::lock(&self.maintenance_windows).clear();
+    }
+
+    pub fn maintenance(&self) -> Option<Maintenance> {
+        active(&poison::lock(&self.maintenance_windows), self.wall())
+    }
+}
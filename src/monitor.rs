@@ -0,0 +1,66 @@
+use audit::AuditEntry;
+use flapping::Dampening;
+use interpact::{CircuitBreaker, Counts, State, Transition};
+use snapshot::Snapshot;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A read-only view of a breaker for dashboards, health endpoints and plugins: it can observe everything but
+/// cannot run calls, record outcomes or change the configuration.
+#[derive(Clone)]
+pub struct Monitor {
+    cb: Arc<CircuitBreaker>,
+}
+
+impl Monitor {
+    pub fn new(cb: Arc<CircuitBreaker>) -> Monitor {
+        Monitor { cb }
+    }
+
+    pub fn name(&self) -> &str {
+        self.cb.name()
+    }
+
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        self.cb.labels()
+    }
+
+    pub fn state(&self) -> State {
+        self.cb.state()
+    }
+
+    pub fn counts(&self) -> Counts {
+        self.cb.counts()
+    }
+
+    pub fn fast_counts(&self) -> Counts {
+        self.cb.fast_counts()
+    }
+
+    pub fn failure_rate(&self) -> f64 {
+        self.cb.counts().failure_rate()
+    }
+
+    pub fn dampening(&self) -> Dampening {
+        self.cb.dampening()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.cb.snapshot()
+    }
+
+    pub fn audit_log(&self) -> Vec<AuditEntry> {
+        self.cb.audit_log()
+    }
+
+    /// The latest transitions, oldest first, up to `Options::history` of them.
+    pub fn history(&self) -> Vec<Transition> {
+        self.cb.lock_inner().history.iter().cloned().collect()
+    }
+}
+
+impl From<Arc<CircuitBreaker>> for Monitor {
+    fn from(cb: Arc<CircuitBreaker>) -> Monitor {
+        Monitor::new(cb)
+    }
+}
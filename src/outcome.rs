@@ -0,0 +1,71 @@
+use context::Context;
+use interpact::CircuitBreaker;
+use std::time;
+
+/// How a call ended. Classifiers decide it, the breaker records it, and hooks and listeners are told about it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum Outcome {
+    Success,
+    /// A success judged slow by `Options::slow_call_duration` or `Options::slow_call_percentile`. It counts as a
+    /// success and in `Counts::slow_calls`.
+    Slow,
+    Failure,
+    /// A failure that counts `weight` times towards `Counts::weighted_failures`, so that severe failure modes
+    /// (e.g. timeouts) can trip rate-based policies faster than mild ones.
+    WeightedFailure(f64),
+    /// Not counted at all; the permit is given back.
+    Ignored,
+    /// The breaker turned the call away, so it never ran.
+    Rejected,
+    /// The caller gave up on the call. It is recorded according to `Options::cancelled_as`.
+    Cancelled,
+}
+
+impl Outcome {
+    pub(crate) fn from_success(success: bool) -> Outcome {
+        if success {
+            Outcome::Success
+        } else {
+            Outcome::Failure
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(*self, Outcome::Success | Outcome::Slow)
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(*self, Outcome::Failure | Outcome::WeightedFailure(_))
+    }
+}
+
+impl CircuitBreaker {
+    /// Records a completed call that was admitted at `started`, then reports its outcome and duration to the
+    /// `on_success`/`on_failure` hooks and to listeners.
+    pub(crate) fn after_call(&self, before: u64, outcome: Outcome, started: time::Instant) {
+        self.after_call_in(before, outcome, started, &Context::default());
+    }
+
+    pub(crate) fn after_call_in(&self, before: u64, outcome: Outcome, started: time::Instant, context: &Context) {
+        let duration = self.now().saturating_duration_since(started);
+        self.after_call_took(before, outcome, duration, context);
+    }
+
+    /// Records a call that took `duration`, judging whether it was slow first.
+    pub(crate) fn after_call_took(&self, before: u64, outcome: Outcome, duration: time::Duration, context: &Context) {
+        if cfg!(feature = "noop") {
+            return;
+        }
+        let slow = match outcome {
+            Outcome::Ignored | Outcome::Rejected | Outcome::Cancelled => false,
+            _ => self.is_slow(duration),
+        };
+        let outcome = match outcome {
+            Outcome::Success if slow => Outcome::Slow,
+            outcome => outcome,
+        };
+        self.after_outcome(before, outcome);
+        self.report_call(before, outcome, duration, context);
+    }
+}
@@ -0,0 +1,217 @@
+use clock::{Clock, SystemClock};
+use errors;
+use interpact::{CircuitBreaker, State};
+use keyed::Keyed;
+use outcome::Outcome;
+use permit::Permit;
+use poison;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync;
+use std::time;
+
+pub struct OutlierOptions {
+    pub interval: time::Duration,
+    pub ejection_time: time::Duration,
+    pub min_endpoints: usize,
+    pub min_requests: u32,
+    pub stdev_factor: f64,
+    pub max_ejection_percent: u32,
+}
+
+impl Default for OutlierOptions {
+    fn default() -> OutlierOptions {
+        OutlierOptions {
+            interval: time::Duration::from_secs(10),
+            ejection_time: time::Duration::from_secs(30),
+            min_endpoints: 3,
+            min_requests: 10,
+            stdev_factor: 1.9,
+            max_ejection_percent: 10,
+        }
+    }
+}
+
+#[derive(Default)]
+struct EndpointStats {
+    successes: u32,
+    failures: u32,
+    latency: time::Duration,
+    ejected_until: Option<time::Instant>,
+}
+
+impl EndpointStats {
+    fn requests(&self) -> u32 {
+        self.successes.saturating_add(self.failures)
+    }
+
+    /// An endpoint without requests has nothing against it, and must not turn the pool's mean into NaN.
+    fn success_rate(&self) -> f64 {
+        match self.requests() {
+            0 => 1.0,
+            requests => f64::from(self.successes) / f64::from(requests),
+        }
+    }
+
+    fn mean_latency(&self) -> f64 {
+        match self.requests() {
+            0 => 0.0,
+            requests => self.latency.as_secs_f64() / f64::from(requests),
+        }
+    }
+
+    fn ejected(&self, now: time::Instant) -> bool {
+        self.ejected_until.is_some_and(|until| until > now)
+    }
+}
+
+struct Pool<K> {
+    endpoints: HashMap<K, EndpointStats>,
+    evaluated_at: time::Instant,
+}
+
+/// Envoy-style outlier detection over a pool of endpoints with one breaker each. Every `interval`, endpoints
+/// whose success rate is more than `stdev_factor` standard deviations below the pool mean, or whose mean latency
+/// is that far above it, are ejected for `ejection_time`.
+pub struct OutlierDetector<K> {
+    breakers: Keyed<K>,
+    options: OutlierOptions,
+    clock: sync::Arc<dyn Clock>,
+    pool: sync::Mutex<Pool<K>>,
+}
+
+fn outliers(values: &[f64], stdev_factor: f64) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, stdev_factor * variance.sqrt())
+}
+
+impl<K: Hash + Eq + Clone> OutlierDetector<K> {
+    pub fn new(factory: fn(key: &K) -> CircuitBreaker, options: OutlierOptions) -> OutlierDetector<K> {
+        OutlierDetector {
+            breakers: Keyed::new(factory),
+            options,
+            clock: sync::Arc::new(SystemClock),
+            pool: sync::Mutex::new(Pool {
+                endpoints: HashMap::new(),
+                evaluated_at: time::Instant::now(),
+            }),
+        }
+    }
+
+    /// Times rounds, ejections and latencies with `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: sync::Arc<dyn Clock>) -> OutlierDetector<K> {
+        poison::lock(&self.pool).evaluated_at = clock.now();
+        self.clock = clock;
+        self
+    }
+
+    pub fn breaker(&self, key: &K) -> sync::Arc<CircuitBreaker> {
+        self.breakers.get(key)
+    }
+
+    pub fn add(&self, key: K) {
+        self.breakers.get(&key);
+        poison::lock(&self.pool).endpoints.entry(key).or_default();
+    }
+
+    pub fn is_ejected(&self, key: &K) -> bool {
+        let now = self.clock.now();
+        poison::lock(&self.pool)
+            .endpoints
+            .get(key)
+            .is_some_and(|stats| stats.ejected(now))
+    }
+
+    /// Endpoints that are neither ejected nor behind an Open circuit.
+    pub fn healthy(&self) -> Vec<K> {
+        let now = self.clock.now();
+        let pool = poison::lock(&self.pool);
+        pool.endpoints
+            .iter()
+            .filter(|&(key, stats)| !stats.ejected(now) && self.breakers.get(key).state() != State::Open)
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    pub fn execute<T, E, F>(&self, key: &K, task: F) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.evaluate_if_due();
+        if self.is_ejected(key) {
+            return Err(errors::CircuitBreakerError {
+                kind: errors::CircuitBreakerErrorKind::EjectedError,
+                message: "The endpoint is ejected as an outlier".into(),
+            });
+        }
+        let cb = self.breakers.get(key);
+        let permit = Permit::acquire(&*cb)?;
+        let started = self.clock.now();
+        let task_result = task();
+        let elapsed = self.clock.now().saturating_duration_since(started);
+        permit.record(Outcome::from_success(task_result.is_ok()));
+        {
+            let mut pool = poison::lock(&self.pool);
+            let stats = pool.endpoints.entry(key.clone()).or_default();
+            if task_result.is_ok() {
+                stats.successes = stats.successes.saturating_add(1);
+            } else {
+                stats.failures = stats.failures.saturating_add(1);
+            }
+            stats.latency = stats.latency.saturating_add(elapsed);
+        }
+        Ok(task_result)
+    }
+
+    fn evaluate_if_due(&self) {
+        let now = self.clock.now();
+        let due = now.saturating_duration_since(poison::lock(&self.pool).evaluated_at) >= self.options.interval;
+        if due {
+            self.evaluate();
+        }
+    }
+
+    /// Runs one detection round over the statistics gathered since the previous round, then resets them.
+    pub fn evaluate(&self) {
+        let now = self.clock.now();
+        let mut pool = poison::lock(&self.pool);
+        pool.evaluated_at = now;
+        let total = pool.endpoints.len();
+        let mut ejected = pool.endpoints.values().filter(|stats| stats.ejected(now)).count();
+        // Like Envoy, always allow ejecting at least one endpoint so that small pools are protected too.
+        let max_ejected = ::std::cmp::max(1, total * self.options.max_ejection_percent as usize / 100);
+
+        // Endpoints without requests are never candidates, even with a `min_requests` of 0.
+        let min_requests = ::std::cmp::max(1, self.options.min_requests);
+        let candidates: Vec<K> = pool
+            .endpoints
+            .iter()
+            .filter(|&(_, stats)| !stats.ejected(now) && stats.requests() >= min_requests)
+            .map(|(key, _)| key.clone())
+            .collect();
+        if candidates.len() >= self.options.min_endpoints {
+            let rates: Vec<f64> = candidates.iter().map(|key| pool.endpoints[key].success_rate()).collect();
+            let latencies: Vec<f64> = candidates.iter().map(|key| pool.endpoints[key].mean_latency()).collect();
+            let (mean_rate, rate_margin) = outliers(&rates, self.options.stdev_factor);
+            let (mean_latency, latency_margin) = outliers(&latencies, self.options.stdev_factor);
+            for (i, key) in candidates.iter().enumerate() {
+                if ejected >= max_ejected {
+                    break;
+                }
+                if rates[i] < mean_rate - rate_margin || latencies[i] > mean_latency + latency_margin {
+                    if let Some(stats) = pool.endpoints.get_mut(key) {
+                        stats.ejected_until = Some(now + self.options.ejection_time);
+                        ejected += 1;
+                    }
+                }
+            }
+        }
+
+        for stats in pool.endpoints.values_mut() {
+            stats.successes = 0;
+            stats.failures = 0;
+            stats.latency = time::Duration::from_secs(0);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use filter;
+use interpact::{CircuitBreaker, Options};
+
+/// Selects the keys an override applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyPattern {
+    Exact(String),
+    Prefix(String),
+    /// `*` matches any run of characters and `?` any single one.
+    Glob(String),
+}
+
+impl KeyPattern {
+    pub fn matches(&self, key: &str) -> bool {
+        match *self {
+            KeyPattern::Exact(ref exact) => key == exact,
+            KeyPattern::Prefix(ref prefix) => key.starts_with(prefix.as_str()),
+            KeyPattern::Glob(ref glob) => filter::matches_glob(glob.as_bytes(), key.as_bytes()),
+        }
+    }
+
+    // Globs are the least specific, then prefixes by length, then exact keys.
+    fn specificity(&self) -> (u8, usize) {
+        match *self {
+            KeyPattern::Glob(_) => (0, 0),
+            KeyPattern::Prefix(ref prefix) => (1, prefix.len()),
+            KeyPattern::Exact(_) => (2, 0),
+        }
+    }
+}
+
+type Override = fn(options: &mut Options);
+
+/// Options for breakers created by key: `base` gives the defaults, and every override whose pattern matches the
+/// key is then applied, from the least specific pattern to the most, so that e.g. an exact key can tighten what a
+/// `payments.*` glob already set.
+pub struct Overrides {
+    base: fn(key: &str) -> Options,
+    rules: Vec<(KeyPattern, Override)>,
+}
+
+impl Overrides {
+    pub fn new(base: fn(key: &str) -> Options) -> Overrides {
+        Overrides { base, rules: Vec::new() }
+    }
+
+    pub fn exact(self, key: &str, apply: Override) -> Overrides {
+        self.rule(KeyPattern::Exact(key.into()), apply)
+    }
+
+    pub fn prefix(self, prefix: &str, apply: Override) -> Overrides {
+        self.rule(KeyPattern::Prefix(prefix.into()), apply)
+    }
+
+    pub fn glob(self, glob: &str, apply: Override) -> Overrides {
+        self.rule(KeyPattern::Glob(glob.into()), apply)
+    }
+
+    pub fn rule(mut self, pattern: KeyPattern, apply: Override) -> Overrides {
+        self.rules.push((pattern, apply));
+        self
+    }
+
+    pub fn options<'k>(&self, key: &'k str) -> Options<'k> {
+        let mut options = (self.base)(key);
+        let mut matching: Vec<_> = self.rules.iter().filter(|&(pattern, _)| pattern.matches(key)).collect();
+        // The sort is stable, so equally specific overrides apply in the order they were added.
+        matching.sort_by_key(|&(pattern, _)| pattern.specificity());
+        for &(_, apply) in matching {
+            apply(&mut options);
+        }
+        options
+    }
+
+    pub fn breaker(&self, key: &str) -> CircuitBreaker {
+        CircuitBreaker::new(self.options(key))
+    }
+}
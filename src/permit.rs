@@ -0,0 +1,87 @@
+use context::Context;
+use errors;
+use interpact::CircuitBreaker;
+use keyed::Keyed;
+use outcome::Outcome;
+use std::hash::Hash;
+use std::ops::Deref;
+use std::sync;
+use std::time;
+
+/// An admitted call whose outcome is reported separately, for middleware that admits a request on the way in
+/// and sees its response on the way out. A permit dropped without being recorded counts as cancelled.
+///
+/// Permits over an `Arc<CircuitBreaker>` are `'static`, so they can be kept in request-local state, as a Rocket
+/// request guard or fairing would.
+#[must_use = "a permit counts as cancelled unless its outcome is recorded"]
+pub struct Permit<B: Deref<Target = CircuitBreaker>> {
+    cb: B,
+    generation: u64,
+    started: time::Instant,
+    recorded: bool,
+}
+
+impl<B: Deref<Target = CircuitBreaker>> Permit<B> {
+    pub fn acquire(cb: B) -> Result<Permit<B>, errors::CircuitBreakerError> {
+        let generation = cb.before_request()?;
+        Ok(Permit::admitted(cb, generation))
+    }
+
+    /// Guards a call the breaker has already admitted as `generation`, so that a task that panics, or a
+    /// future that is dropped, gives the call back instead of holding its place in flight.
+    pub(crate) fn admitted(cb: B, generation: u64) -> Permit<B> {
+        let started = cb.now();
+        Permit {
+            cb,
+            generation,
+            started,
+            recorded: false,
+        }
+    }
+
+    pub fn record(self, outcome: Outcome) {
+        self.record_in(outcome, &Context::default())
+    }
+
+    pub(crate) fn record_in(mut self, outcome: Outcome, context: &Context) {
+        self.recorded = true;
+        self.cb.after_call_in(self.generation, outcome, self.started, context);
+    }
+
+    pub fn success(self) {
+        self.record(Outcome::Success)
+    }
+
+    pub fn failure(self) {
+        self.record(Outcome::Failure)
+    }
+
+    pub fn elapsed(&self) -> time::Duration {
+        self.cb.now().saturating_duration_since(self.started)
+    }
+
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.cb
+    }
+}
+
+impl<B: Deref<Target = CircuitBreaker>> Drop for Permit<B> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.cb.after_call(self.generation, Outcome::Cancelled, self.started);
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn permit(&self) -> Result<Permit<&CircuitBreaker>, errors::CircuitBreakerError> {
+        Permit::acquire(self)
+    }
+}
+
+impl<K: Hash + Eq + Clone> Keyed<K> {
+    /// A permit from the breaker for `key`, e.g. the route of an inbound request.
+    pub fn permit(&self, key: &K) -> Result<Permit<sync::Arc<CircuitBreaker>>, errors::CircuitBreakerError> {
+        Permit::acquire(self.get(key))
+    }
+}
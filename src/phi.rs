@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+use std::time;
+
+/// A phi-accrual failure detector (Hayashibara et al., as used by Akka and Cassandra). It learns the distribution
+/// of intervals between successes and reports `phi`, the suspicion that the dependency is down given how long it
+/// has been since the last success: phi = 1 means about a 10% chance of a false suspicion, 2 about 1%, and so on.
+#[derive(Debug, Clone)]
+pub struct PhiAccrual {
+    intervals: VecDeque<f64>,
+    max_samples: usize,
+    min_std_deviation: f64,
+    last: Option<time::Instant>,
+}
+
+impl Default for PhiAccrual {
+    fn default() -> PhiAccrual {
+        PhiAccrual::new(200, time::Duration::from_millis(100))
+    }
+}
+
+impl PhiAccrual {
+    pub fn new(max_samples: usize, min_std_deviation: time::Duration) -> PhiAccrual {
+        PhiAccrual {
+            intervals: VecDeque::with_capacity(max_samples),
+            max_samples,
+            min_std_deviation: min_std_deviation.as_secs_f64() * 1000.0,
+            last: None,
+        }
+    }
+
+    pub fn heartbeat(&mut self, now: time::Instant) {
+        if let Some(last) = self.last {
+            if self.intervals.len() >= self.max_samples {
+                self.intervals.pop_front();
+            }
+            self.intervals
+                .push_back(now.saturating_duration_since(last).as_secs_f64() * 1000.0);
+        }
+        self.last = Some(now);
+    }
+
+    pub fn phi(&self, now: time::Instant) -> f64 {
+        let last = match self.last {
+            Some(last) if !self.intervals.is_empty() => last,
+            _ => return 0.0,
+        };
+        let elapsed = now.saturating_duration_since(last).as_secs_f64() * 1000.0;
+        let samples = self.intervals.len() as f64;
+        let mean = self.intervals.iter().sum::<f64>() / samples;
+        let variance = self.intervals.iter().map(|interval| (interval - mean).powi(2)).sum::<f64>() / samples;
+        let std_deviation = variance.sqrt().max(self.min_std_deviation);
+
+        // Logistic approximation of the normal distribution's CDF, as in Akka.
+        let y = (elapsed - mean) / std_deviation;
+        let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+        if elapsed > mean {
+            -(e / (1.0 + e)).log10()
+        } else {
+            -(1.0 - 1.0 / (1.0 + e)).log10()
+        }
+    }
+}
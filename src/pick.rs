@@ -0,0 +1,35 @@
+use interpact::State;
+use keyed::Keyed;
+use std::hash::Hash;
+use std::sync::atomic::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    RoundRobin,
+    LeastFailures,
+}
+
+impl<K: Hash + Eq + Clone> Keyed<K> {
+    /// Picks a key whose circuit is Closed, so callers can route around open circuits. Returns `None` if
+    /// there is no such key.
+    pub fn pick(&self, strategy: Pick) -> Option<K> {
+        let closed: Vec<_> = self
+            .breakers()
+            .into_iter()
+            .filter(|(_, cb)| cb.state() == State::Closed)
+            .collect();
+        if closed.is_empty() {
+            return None;
+        }
+        match strategy {
+            Pick::RoundRobin => {
+                let next = self.cursor.fetch_add(1, Ordering::Relaxed);
+                Some(closed[next % closed.len()].0.clone())
+            }
+            Pick::LeastFailures => closed
+                .into_iter()
+                .min_by_key(|(_, cb)| cb.counts().total_failures())
+                .map(|(key, _)| key),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use recording::Recording;
+
+/// How important a call is. While a breaker is degraded, calls below `Options::shed_below` are rejected
+/// so that the remaining permits go to more important work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Priority {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl CircuitBreaker {
+    pub fn execute_with_priority<T, E, F>(
+        &self,
+        priority: Priority,
+        task: F,
+    ) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = Permit::admitted(self, self.before_request_with(priority, None)?);
+        let task_result = task();
+        permit.record(Outcome::from_success(task_result.is_ok()));
+        Ok(task_result)
+    }
+
+    pub fn recording_with_priority(&self, priority: Priority) -> Result<Recording<'_>, errors::CircuitBreakerError> {
+        let generation = self.before_request_with(priority, None)?;
+        Ok(Recording::new(self, generation))
+    }
+}
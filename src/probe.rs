@@ -0,0 +1,37 @@
+use std::time;
+
+/// Shapes probing while HalfOpen: at most `probes` calls per `per`, let through one at a time and spread
+/// evenly, instead of `max_requests` calls at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProbeRate {
+    pub probes: u32,
+    pub per: time::Duration,
+}
+
+/// A token bucket holding at most one token, refilled at the `ProbeRate`.
+pub(crate) struct ProbeBucket {
+    tokens: f64,
+    refilled: time::Instant,
+}
+
+impl ProbeBucket {
+    pub(crate) fn new(now: time::Instant) -> ProbeBucket {
+        ProbeBucket {
+            tokens: 1.0,
+            refilled: now,
+        }
+    }
+
+    pub(crate) fn take(&mut self, rate: ProbeRate, now: time::Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * f64::from(rate.probes) / rate.per.as_secs_f64()).min(1.0);
+        self.refilled = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhileOpen {
+    Drop,
+    Error,
+    /// Hand items to the callback set with `Producer::on_divert`, e.g. to park them in a local store.
+    Divert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sent {
+    Delivered,
+    Buffered,
+    Dropped,
+    Diverted,
+}
+
+fn ignore_dropped() {}
+
+/// Wraps a `send(item) -> Result` style publisher so every send goes through a breaker.
+pub struct Producer<I, B, F> {
+    cb: B,
+    send: F,
+    while_open: WhileOpen,
+    buffer: VecDeque<I>,
+    capacity: usize,
+    /// Set by `Producer::buffering`, the only mode that has to keep a copy of an item while sending it.
+    clone_item: Option<fn(&I) -> I>,
+    dropped: u64,
+    on_divert: Option<fn(item: I)>,
+    on_dropped: fn(),
+}
+
+impl<I, B, F, E> Producer<I, B, F>
+where
+    I: Clone,
+    B: Deref<Target = CircuitBreaker>,
+    F: FnMut(I) -> Result<(), E>,
+{
+    /// Keeps up to `capacity` items while the circuit is open and publishes them, in order, ahead of the
+    /// next send once it lets calls through again. Items arriving at a full buffer are dropped.
+    pub fn buffering(cb: B, capacity: usize, send: F) -> Producer<I, B, F> {
+        Producer {
+            capacity,
+            clone_item: Some(I::clone),
+            ..Producer::new(cb, WhileOpen::Drop, send)
+        }
+    }
+}
+
+impl<I, B, F, E> Producer<I, B, F>
+where
+    B: Deref<Target = CircuitBreaker>,
+    F: FnMut(I) -> Result<(), E>,
+{
+    pub fn new(cb: B, while_open: WhileOpen, send: F) -> Producer<I, B, F> {
+        Producer {
+            cb,
+            send,
+            while_open,
+            buffer: VecDeque::new(),
+            capacity: 0,
+            clone_item: None,
+            dropped: 0,
+            on_divert: None,
+            on_dropped: ignore_dropped,
+        }
+    }
+
+    /// Where items go while the circuit is open under `WhileOpen::Divert`. Without it they are dropped.
+    pub fn on_divert(mut self, on_divert: fn(item: I)) -> Producer<I, B, F> {
+        self.on_divert = Some(on_divert);
+        self
+    }
+
+    /// Called for every dropped item, e.g. to bump a metric.
+    pub fn on_dropped(mut self, on_dropped: fn()) -> Producer<I, B, F> {
+        self.on_dropped = on_dropped;
+        self
+    }
+
+    fn drop_item(&mut self) -> Sent {
+        self.dropped += 1;
+        (self.on_dropped)();
+        Sent::Dropped
+    }
+
+    /// Sends `item`, after anything still buffered so that publishing order is kept. If a buffered item
+    /// cannot be sent, `item` is buffered behind it (or dropped, if the buffer is full); the failure is recorded
+    /// with the breaker, and `flush` reports it.
+    pub fn send(&mut self, item: I) -> Result<Result<Sent, E>, errors::CircuitBreakerError> {
+        if !matches!(self.flush(), Ok(Ok(()))) {
+            return Ok(Ok(self.enqueue(item)));
+        }
+        let err = match Permit::acquire(&*self.cb) {
+            Ok(permit) => {
+                let result = (self.send)(item);
+                permit.record(Outcome::from_success(result.is_ok()));
+                return Ok(result.map(|_| Sent::Delivered));
+            }
+            Err(err) => err,
+        };
+        if self.clone_item.is_some() {
+            return Ok(Ok(self.enqueue(item)));
+        }
+        match self.while_open {
+            WhileOpen::Error => Err(err),
+            WhileOpen::Drop => Ok(Ok(self.drop_item())),
+            WhileOpen::Divert => match self.on_divert {
+                Some(on_divert) => {
+                    on_divert(item);
+                    Ok(Ok(Sent::Diverted))
+                }
+                None => Ok(Ok(self.drop_item())),
+            },
+        }
+    }
+
+    /// Publishes buffered items in order, stopping at the first rejection or failed send. An item whose send
+    /// failed stays at the front of the buffer.
+    pub fn flush(&mut self) -> Result<Result<(), E>, errors::CircuitBreakerError> {
+        let clone_item = match self.clone_item {
+            Some(clone_item) => clone_item,
+            None => return Ok(Ok(())),
+        };
+        while let Some(item) = self.buffer.pop_front() {
+            let permit = match Permit::acquire(&*self.cb) {
+                Ok(permit) => permit,
+                Err(err) => {
+                    self.buffer.push_front(item);
+                    return Err(err);
+                }
+            };
+            let result = (self.send)(clone_item(&item));
+            permit.record(Outcome::from_success(result.is_ok()));
+            if let Err(err) = result {
+                self.buffer.push_front(item);
+                return Ok(Err(err));
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    fn enqueue(&mut self, item: I) -> Sent {
+        if self.buffer.len() < self.capacity {
+            self.buffer.push_back(item);
+            Sent::Buffered
+        } else {
+            self.drop_item()
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use audit::AuditAction;
+use clock::Clock;
+use interpact::{CircuitBreaker, TripPolicy};
+use std::collections::HashMap;
+use std::time;
+
+/// A named set of thresholds. A breaker starts with the "default" profile built from its `Options`.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub max_requests: u32,
+    pub success_threshold: u32,
+    pub interval: time::Duration,
+    pub timeout: time::Duration,
+    pub trip_policy: TripPolicy,
+}
+
+/// A daily window, as offsets from midnight UTC, during which `profile` is active. Windows whose `until`
+/// is before `from` wrap around midnight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileWindow {
+    pub from: time::Duration,
+    pub until: time::Duration,
+    pub profile: String,
+}
+
+const DAY: u64 = 24 * 60 * 60;
+
+impl ProfileWindow {
+    pub fn is_active(&self, now: time::SystemTime) -> bool {
+        let since_midnight = match now.duration_since(time::UNIX_EPOCH) {
+            Ok(since_epoch) => time::Duration::from_secs(since_epoch.as_secs() % DAY),
+            Err(_) => return false,
+        };
+        if self.from <= self.until {
+            self.from <= since_midnight && since_midnight < self.until
+        } else {
+            self.from <= since_midnight || since_midnight < self.until
+        }
+    }
+}
+
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+
+pub(crate) struct Profiles {
+    pub(crate) active: Profile,
+    profiles: HashMap<String, Profile>,
+    windows: Vec<ProfileWindow>,
+    scheduled: String,
+}
+
+impl Profiles {
+    pub(crate) fn new(default: Profile) -> Profiles {
+        let mut profiles = HashMap::new();
+        profiles.insert(default.name.clone(), default.clone());
+        Profiles {
+            active: default,
+            profiles,
+            windows: Vec::new(),
+            scheduled: String::from(DEFAULT_PROFILE),
+        }
+    }
+
+    /// Switches to the profile the schedule asks for whenever that changes, so a manual switch holds until
+    /// the next window boundary.
+    pub(crate) fn apply_schedule(&mut self, clock: &dyn Clock) {
+        if self.windows.is_empty() {
+            return;
+        }
+        let now = clock.wall();
+        let scheduled = self
+            .windows
+            .iter()
+            .find(|window| window.is_active(now))
+            .map_or(DEFAULT_PROFILE, |window| window.profile.as_str());
+        if scheduled != self.scheduled {
+            self.scheduled = String::from(scheduled);
+            if let Some(profile) = self.profiles.get(scheduled) {
+                self.active = profile.clone();
+            }
+        }
+    }
+
+    fn switch(&mut self, name: &str) -> bool {
+        match self.profiles.get(name) {
+            Some(profile) => {
+                self.active = profile.clone();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Adds `profile`, or replaces the one with the same name. Replacing the active profile takes effect
+    /// right away.
+    pub fn add_profile(&self, profile: Profile) {
+        self.audit(AuditAction::AddProfile(profile.name.clone()), None);
+        let mut inner = self.lock_inner();
+        if profile.name == inner.profiles.active.name {
+            inner.profiles.active = profile.clone();
+        }
+        inner.profiles.profiles.insert(profile.name.clone(), profile);
+    }
+
+    pub fn schedule_profile(&self, window: ProfileWindow) {
+        self.audit(AuditAction::ScheduleProfile(window.profile.clone()), None);
+        let mut inner = self.lock_inner();
+        inner.profiles.windows.push(window);
+        inner.profiles.apply_schedule(self.clock());
+    }
+
+    /// Makes the named profile active right away. The state, counts and timers are kept. Returns false, and
+    /// leaves the audit log alone, if there is no profile by that name.
+    pub fn switch_profile(&self, name: &str) -> bool {
+        let switched = self.lock_inner().profiles.switch(name);
+        if switched {
+            self.audit(AuditAction::SwitchProfile(name.into()), None);
+        }
+        switched
+    }
+
+    pub fn profile(&self) -> Profile {
+        self.lock_inner().profiles.active.clone()
+    }
+}
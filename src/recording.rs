@@ -0,0 +1,58 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use std::time;
+
+/// An admitted call whose outcome is recorded as a failure on drop unless completed explicitly.
+pub struct Recording<'a> {
+    cb: &'a CircuitBreaker,
+    generation: u64,
+    started: time::Instant,
+    completed: bool,
+}
+
+impl<'a> Recording<'a> {
+    pub(crate) fn new(cb: &'a CircuitBreaker, generation: u64) -> Recording<'a> {
+        Recording {
+            cb,
+            generation,
+            started: cb.now(),
+            completed: false,
+        }
+    }
+
+    pub fn complete_success(mut self) {
+        self.complete(true);
+    }
+
+    pub fn complete_failure(mut self) {
+        self.complete(false);
+    }
+
+    /// Records the call according to `Options::cancelled_as` instead of as a success or failure.
+    pub fn complete_cancelled(mut self) {
+        self.completed = true;
+        self.cb.after_call(self.generation, Outcome::Cancelled, self.started);
+    }
+
+    fn complete(&mut self, success: bool) {
+        self.completed = true;
+        self.cb
+            .after_call(self.generation, Outcome::from_success(success), self.started);
+    }
+}
+
+impl<'a> Drop for Recording<'a> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.complete(false);
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn recording(&self) -> Result<Recording<'_>, errors::CircuitBreakerError> {
+        let generation = self.before_request()?;
+        Ok(Recording::new(self, generation))
+    }
+}
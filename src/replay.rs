@@ -0,0 +1,134 @@
+use clock::{Clock, ManualClock, SystemClock};
+use context::Context;
+use interpact::{CircuitBreaker, Options, Transition};
+use listener::{CallEvent, Listener};
+use outcome::Outcome;
+use poison;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+/// One call as a breaker saw it: when it finished, relative to the start of the trace, how it ended and how
+/// long it took.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TracedCall {
+    pub at: time::Duration,
+    pub outcome: Outcome,
+    pub latency: time::Duration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Trace {
+    pub calls: Vec<TracedCall>,
+}
+
+/// A listener that records the calls a breaker sees into a `Trace`. Calls the breaker rejected never ran, so
+/// they have no outcome to replay and are left out. Slow calls are recorded as successes, so that a replay
+/// judges them against its own slow-call settings. Clones share the trace.
+#[derive(Clone)]
+pub struct Recorder {
+    clock: Arc<dyn Clock>,
+    start: time::Instant,
+    calls: Arc<Mutex<Vec<TracedCall>>>,
+}
+
+impl Recorder {
+    /// Times the trace with the system clock.
+    pub fn new() -> Recorder {
+        Recorder::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Times the trace with `cb`'s clock, for breakers run on a `ManualClock` or in a `Simulation`.
+    pub fn for_breaker(cb: &CircuitBreaker) -> Recorder {
+        Recorder::with_clock(cb.shared_clock())
+    }
+
+    fn with_clock(clock: Arc<dyn Clock>) -> Recorder {
+        Recorder {
+            start: clock.now(),
+            clock,
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn trace(&self) -> Trace {
+        Trace {
+            calls: poison::lock(&self.calls).clone(),
+        }
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Recorder {
+        Recorder::new()
+    }
+}
+
+impl Listener for Recorder {
+    fn on_transition(&self, _transition: &Transition) {}
+
+    fn on_call(&self, call: &CallEvent) {
+        if call.outcome == Outcome::Rejected {
+            return;
+        }
+        poison::lock(&self.calls).push(TracedCall {
+            at: self.clock.now().saturating_duration_since(self.start),
+            outcome: unjudged(call.outcome),
+            latency: call.duration,
+        });
+    }
+}
+
+/// A success as it was before the breaker judged it slow.
+fn unjudged(outcome: Outcome) -> Outcome {
+    match outcome {
+        Outcome::Slow => Outcome::Success,
+        outcome => outcome,
+    }
+}
+
+/// How a configuration fared against a trace.
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    /// Every state change, with when it happened relative to the start of the trace.
+    pub transitions: Vec<(time::Duration, Transition)>,
+    pub admitted: u64,
+    pub rejected: u64,
+}
+
+impl Trace {
+    /// Runs the trace through a fresh breaker built from `options`, on a manual clock so that it takes no real
+    /// time. Calls are replayed one at a time, at the moment they finished; a call the breaker rejects is
+    /// counted and its outcome dropped, as it would have been live. Whether a success was slow is judged afresh
+    /// from its latency. `options.clock` is replaced.
+    pub fn replay(&self, mut options: Options) -> Replay {
+        let clock = Arc::new(ManualClock::new());
+        options.clock = Some(clock.clone());
+        let cb = CircuitBreaker::new(options);
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        {
+            let clock = clock.clone();
+            let transitions = transitions.clone();
+            let _ = cb.add_listener(move |transition: &Transition| {
+                poison::lock(&transitions).push((clock.elapsed(), transition.clone()));
+            });
+        }
+        let mut calls = self.calls.clone();
+        calls.sort_by_key(|call| call.at);
+        let context = Context::default();
+        let mut replay = Replay::default();
+        for call in calls {
+            clock.set(call.at);
+            match cb.before_request() {
+                Ok(generation) => {
+                    replay.admitted += 1;
+                    cb.after_call_took(generation, unjudged(call.outcome), call.latency, &context);
+                }
+                Err(_) => replay.rejected += 1,
+            }
+        }
+        replay.transitions = poison::lock(&transitions).clone();
+        replay
+    }
+}
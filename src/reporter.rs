@@ -0,0 +1,212 @@
+use interpact::State;
+use keyed::Keyed;
+use poison;
+use snapshot::Snapshot;
+use std::error;
+use std::fmt::Write as FmtWrite;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::panic;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time;
+
+/// Where a `Reporter` publishes snapshots, such as a Prometheus pushgateway, statsd or a log.
+pub trait Sink: Send {
+    fn publish(&mut self, snapshots: &[Snapshot]) -> Result<(), Box<dyn error::Error + Send + Sync>>;
+}
+
+/// Writes one line per breaker to `W`, for logs and stdout.
+pub struct Lines<W> {
+    writer: W,
+}
+
+impl<W: Write + Send> Lines<W> {
+    pub fn new(writer: W) -> Lines<W> {
+        Lines { writer }
+    }
+}
+
+impl<W: Write + Send> Sink for Lines<W> {
+    fn publish(&mut self, snapshots: &[Snapshot]) -> Result<(), Box<dyn error::Error + Send + Sync>> {
+        for snapshot in snapshots {
+            writeln!(
+                self.writer,
+                "circuit_breaker name={} state={} generation={} requests={} successes={} failures={} rejected={}",
+                snapshot.name,
+                snapshot.state,
+                snapshot.generation,
+                snapshot.counts.requests(),
+                snapshot.counts.total_successes(),
+                snapshot.counts.total_failures(),
+                snapshot.counts.rejected(),
+            )?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Sends statsd gauges over UDP, one datagram per breaker, named `{prefix}.{breaker}.{metric}`. Characters
+/// that statsd reserves are replaced in breaker names.
+pub struct Statsd {
+    socket: UdpSocket,
+    prefix: String,
+}
+
+impl Statsd {
+    pub fn new<A: ToSocketAddrs>(addr: A, prefix: &str) -> io::Result<Statsd> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(addr)?;
+        Ok(Statsd {
+            socket,
+            prefix: prefix.into(),
+        })
+    }
+}
+
+fn statsd_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            ':' | '|' | '@' | '#' | '\n' | ' ' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+impl Sink for Statsd {
+    fn publish(&mut self, snapshots: &[Snapshot]) -> Result<(), Box<dyn error::Error + Send + Sync>> {
+        for snapshot in snapshots {
+            let name = statsd_name(&snapshot.name);
+            let state = match snapshot.state {
+                State::Closed => 0,
+                State::HalfOpen => 1,
+                State::Open => 2,
+            };
+            let counts = &snapshot.counts;
+            let mut datagram = String::new();
+            for &(metric, value) in &[
+                ("state", state as f64),
+                ("requests", counts.requests() as f64),
+                ("successes", counts.total_successes() as f64),
+                ("failures", counts.total_failures() as f64),
+                ("rejected", counts.rejected() as f64),
+                ("failure_rate", counts.failure_rate()),
+            ] {
+                let _ = writeln!(datagram, "{}.{}.{}:{}|g", self.prefix, name, metric, value);
+            }
+            self.socket.send(datagram.trim_end().as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Status {
+    published: AtomicU64,
+    failed: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+/// Publishes the snapshots of every breaker in a `Keyed` set on a dedicated thread every `interval`. A sink
+/// that fails or panics only loses that round; the error is kept for `last_error` and the next round runs on
+/// schedule. The thread stops when the reporter is stopped or dropped.
+pub struct Reporter {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    status: Arc<Status>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Reporter {
+    pub fn spawn<K, S>(breakers: Arc<Keyed<K>>, interval: time::Duration, mut sink: S) -> Reporter
+    where
+        K: Hash + Eq + Clone + Send + Sync + 'static,
+        S: Sink + 'static,
+    {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let status = Arc::new(Status::default());
+        let thread = {
+            let stop = stop.clone();
+            let status = status.clone();
+            thread::Builder::new()
+                .name("interpact-reporter".into())
+                .spawn(move || {
+                    let mut next = time::Instant::now() + interval;
+                    loop {
+                        {
+                            let (ref stopped, ref condvar) = *stop;
+                            let mut stopped = poison::lock(stopped);
+                            loop {
+                                if *stopped {
+                                    return;
+                                }
+                                let now = time::Instant::now();
+                                if now >= next {
+                                    break;
+                                }
+                                stopped = poison::wait_timeout(condvar, stopped, next - now);
+                            }
+                        }
+                        let snapshots: Vec<Snapshot> =
+                            breakers.snapshot_all().into_iter().map(|(_, snapshot)| snapshot).collect();
+                        let error = match panic::catch_unwind(panic::AssertUnwindSafe(|| sink.publish(&snapshots))) {
+                            Ok(Ok(())) => None,
+                            Ok(Err(err)) => Some(err.to_string()),
+                            Err(_) => Some("the sink panicked".into()),
+                        };
+                        match error {
+                            None => status.published.fetch_add(1, Ordering::Relaxed),
+                            Some(error) => {
+                                *poison::lock(&status.last_error) = Some(error);
+                                status.failed.fetch_add(1, Ordering::Relaxed)
+                            }
+                        };
+                        // Rounds missed by a slow sink are skipped rather than run back to back.
+                        next = ::std::cmp::max(next + interval, time::Instant::now());
+                    }
+                })
+                .expect("failed to spawn the reporter thread")
+        };
+        Reporter {
+            stop,
+            status,
+            thread: Some(thread),
+        }
+    }
+
+    /// How many rounds the sink published successfully.
+    pub fn published(&self) -> u64 {
+        self.status.published.load(Ordering::Relaxed)
+    }
+
+    /// How many rounds failed because the sink returned an error or panicked.
+    pub fn failed(&self) -> u64 {
+        self.status.failed.load(Ordering::Relaxed)
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        poison::lock(&self.status.last_error).clone()
+    }
+
+    /// Stops the thread, waiting for a round in progress to finish.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let (ref stopped, ref condvar) = *self.stop;
+        *poison::lock(stopped) = true;
+        condvar.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
@@ -0,0 +1,16 @@
+use interpact::CircuitBreaker;
+
+/// Records an already obtained result into a breaker, for calls whose admission is decided elsewhere.
+pub trait ResultExt<T, E> {
+    fn record_in(self, cb: &CircuitBreaker) -> Result<T, E>;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+    fn record_in(self, cb: &CircuitBreaker) -> Result<T, E> {
+        match self {
+            Ok(_) => cb.record_success(),
+            Err(_) => cb.record_failure(),
+        }
+        self
+    }
+}
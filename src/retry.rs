@@ -0,0 +1,81 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use poison;
+use std::sync;
+
+/// A token bucket that caps retries relative to successful traffic, like tower's retry budget. Every successful
+/// first attempt deposits `retry_ratio` tokens, up to `max_tokens`, and every retry withdraws one token.
+pub struct RetryBudget {
+    retry_ratio: f64,
+    max_tokens: f64,
+    tokens: sync::Mutex<f64>,
+}
+
+impl RetryBudget {
+    pub fn new(retry_ratio: f64, max_tokens: f64) -> RetryBudget {
+        RetryBudget {
+            retry_ratio,
+            max_tokens,
+            tokens: sync::Mutex::new(max_tokens),
+        }
+    }
+
+    pub fn deposit(&self) {
+        let mut tokens = poison::lock(&self.tokens);
+        *tokens = (*tokens + self.retry_ratio).min(self.max_tokens);
+    }
+
+    pub fn withdraw(&self) -> bool {
+        let mut tokens = poison::lock(&self.tokens);
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn tokens(&self) -> f64 {
+        *poison::lock(&self.tokens)
+    }
+}
+
+impl CircuitBreaker {
+    /// Runs `task` up to `max_attempts` times, each attempt admitted and recorded on its own. Retries stop as soon
+    /// as the circuit rejects an attempt or `budget` has no token left, returning the last outcome: the error of
+    /// the last attempt that ran, rather than the rejection of the retry that did not.
+    pub fn execute_with_retry<T, E, F>(
+        &self,
+        budget: &RetryBudget,
+        max_attempts: u32,
+        mut task: F,
+    ) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        F: FnMut() -> Result<T, E>,
+    {
+        let mut attempt = 1;
+        let mut last = None;
+        loop {
+            let permit = match (Permit::acquire(self), last) {
+                (Ok(permit), _) => permit,
+                (Err(_), Some(last)) => return Ok(last),
+                (Err(err), None) => return Err(err),
+            };
+            let task_result = task();
+            permit.record(Outcome::from_success(task_result.is_ok()));
+            match task_result {
+                Ok(_) if attempt == 1 => {
+                    budget.deposit();
+                    return Ok(task_result);
+                }
+                Err(_) if attempt < max_attempts && budget.withdraw() => {
+                    attempt += 1;
+                    last = Some(task_result);
+                }
+                _ => return Ok(task_result),
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// The source of randomness for jitter and sampling. Supplying a seeded one makes runs reproducible and avoids
+/// the need for OS entropy.
+pub trait Rng: Send {
+    fn next_u64(&mut self) -> u64;
+
+    /// A number uniformly distributed in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A small xorshift64* generator. Not suitable for anything security related.
+#[derive(Debug, Clone)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        XorShift {
+            // The all-zero state would only ever produce zeros.
+            state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed },
+        }
+    }
+}
+
+impl Default for XorShift {
+    /// Seeded from the standard library's per-process random hasher keys.
+    fn default() -> XorShift {
+        XorShift::new(RandomState::new().build_hasher().finish())
+    }
+}
+
+impl Rng for XorShift {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
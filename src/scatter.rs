@@ -0,0 +1,118 @@
+use interpact::CircuitBreaker;
+use keyed::Keyed;
+use outcome::Outcome;
+use permit::Permit;
+use std::hash::Hash;
+use std::panic;
+use std::sync;
+use std::thread;
+
+#[cfg(feature = "async")]
+use futures::future::{self, Future, FutureExt};
+
+#[derive(Debug)]
+pub struct Gathered<K, T, E> {
+    pub responses: Vec<(K, T)>,
+    pub failures: Vec<(K, E)>,
+    pub skipped: Vec<K>,
+}
+
+impl<K, T, E> Gathered<K, T, E> {
+    fn new() -> Gathered<K, T, E> {
+        Gathered {
+            responses: Vec::new(),
+            failures: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, key: K, result: Option<Result<T, E>>) {
+        match result {
+            Some(Ok(response)) => self.responses.push((key, response)),
+            Some(Err(err)) => self.failures.push((key, err)),
+            None => self.skipped.push(key),
+        }
+    }
+}
+
+/// A call that panics, or a future that is dropped, leaves its permit to count as cancelled.
+fn admit<K: Hash + Eq + Clone>(keyed: &Keyed<K>, key: &K) -> Option<Permit<sync::Arc<CircuitBreaker>>> {
+    keyed.permit(key).ok()
+}
+
+impl<K: Hash + Eq + Clone> Keyed<K> {
+    /// Calls `task` for every key whose circuit admits the call, each on its own thread, and records
+    /// every outcome in that key's breaker. Keys whose circuits reject the call are reported as skipped.
+    /// If a call panics, the panic is resumed here once every call has finished.
+    pub fn scatter_gather<T, E, I, F>(&self, keys: I, task: F) -> Gathered<K, T, E>
+    where
+        I: IntoIterator<Item = K>,
+        K: Send,
+        F: Fn(&K) -> Result<T, E> + Sync,
+        T: Send,
+        E: Send,
+    {
+        let task = &task;
+        let results: Vec<(K, Option<Result<T, E>>)> = thread::scope(|scope| {
+            let handles: Vec<_> = keys
+                .into_iter()
+                .map(|key| {
+                    let admitted = admit(self, &key);
+                    let handle = admitted.map(|permit| {
+                        let key = key.clone();
+                        scope.spawn(move || {
+                            let result = task(&key);
+                            permit.record(Outcome::from_success(result.is_ok()));
+                            result
+                        })
+                    });
+                    (key, handle)
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(key, handle)| {
+                    let result = handle.map(|handle| handle.join().unwrap_or_else(|panic| panic::resume_unwind(panic)));
+                    (key, result)
+                })
+                .collect()
+        });
+        let mut gathered = Gathered::new();
+        for (key, result) in results {
+            gathered.push(key, result);
+        }
+        gathered
+    }
+
+    /// Like `scatter_gather`, with the calls to every admitted key running concurrently as futures.
+    #[cfg(feature = "async")]
+    pub fn scatter_gather_async<'a, T, E, I, F, Fut>(&'a self, keys: I, task: F) -> impl Future<Output = Gathered<K, T, E>> + 'a
+    where
+        I: IntoIterator<Item = K>,
+        F: Fn(&K) -> Fut,
+        Fut: Future<Output = Result<T, E>> + 'a,
+        K: 'a,
+        T: 'a,
+        E: 'a,
+    {
+        let calls: Vec<_> = keys
+            .into_iter()
+            .map(|key| match admit(self, &key) {
+                Some(permit) => task(&key)
+                    .map(move |result| {
+                        permit.record(Outcome::from_success(result.is_ok()));
+                        (key, Some(result))
+                    })
+                    .left_future(),
+                None => future::ready((key, None)).right_future(),
+            })
+            .collect();
+        future::join_all(calls).map(|results| {
+            let mut gathered = Gathered::new();
+            for (key, result) in results {
+                gathered.push(key, result);
+            }
+            gathered
+        })
+    }
+}
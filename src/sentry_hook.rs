@@ -0,0 +1,168 @@
+//! Sentry-shaped reporting: transitions become breadcrumbs, and a circuit that opens, or stays open for too
+//! long, raises an event. The closures are where the records meet the Sentry client in use:
+//!
+//! ```ignore
+//! let hook = SentryHook::new(
+//!     |record: &Record| sentry::add_breadcrumb(Breadcrumb {
+//!         category: Some("circuit_breaker".into()),
+//!         message: Some(record.to_string()),
+//!         ..Default::default()
+//!     }),
+//!     |alert: &Alert| {
+//!         sentry::with_scope(
+//!             |scope| alert.tags.iter().for_each(|(k, v)| scope.set_tag(k, v)),
+//!             || sentry::capture_message(&alert.message, sentry::Level::Error),
+//!         );
+//!     },
+//! );
+//! ```
+
+use audit::AuditEntry;
+use clock::{Clock, SystemClock};
+use context::Context;
+use interpact::{State, Transition};
+use listener::{CallEvent, Listener};
+use logging::{self, Record};
+use outcome::Outcome;
+use poison;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time;
+
+/// A circuit that opened or stayed open too long, tagged with the breaker's name, labels and the context of
+/// its last failed call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    pub message: String,
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Default)]
+struct Tracked {
+    /// When the circuit last opened from Closed; failed probes that reopen it do not start a new episode.
+    opened_at: Option<time::Instant>,
+    stuck_reported: bool,
+    labels: BTreeMap<String, String>,
+    last_failure: Option<Context>,
+}
+
+/// A listener that hands every transition to `breadcrumb` and raises an `Alert` through `alert` when a circuit
+/// opens. Until it closes again, reopening after failed probes belongs to the same episode and raises nothing
+/// new. With `stuck_after`, a circuit that has not closed that long after opening raises a second alert, once
+/// per episode; it is noticed on the first rejected call or reopening past the limit.
+pub struct SentryHook<B, A> {
+    breadcrumb: B,
+    alert: A,
+    stuck_after: Option<time::Duration>,
+    clock: Arc<dyn Clock>,
+    breakers: Mutex<HashMap<String, Tracked>>,
+}
+
+impl<B, A> SentryHook<B, A>
+where
+    B: Fn(&Record) + Send + Sync,
+    A: Fn(&Alert) + Send + Sync,
+{
+    pub fn new(breadcrumb: B, alert: A) -> SentryHook<B, A> {
+        SentryHook {
+            breadcrumb,
+            alert,
+            stuck_after: None,
+            clock: Arc::new(SystemClock),
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn stuck_after(mut self, stuck_after: time::Duration) -> SentryHook<B, A> {
+        self.stuck_after = Some(stuck_after);
+        self
+    }
+
+    /// Times episodes with `clock`, which should be the one the breakers were built with.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> SentryHook<B, A> {
+        self.clock = clock;
+        self
+    }
+
+    /// An alert for a circuit that has stayed open past `stuck_after`, unless this episode already raised one.
+    fn stuck(&self, name: &str, tracked: &mut Tracked) -> Option<Alert> {
+        let stuck_after = self.stuck_after?;
+        let open_for = self.clock.now().saturating_duration_since(tracked.opened_at?);
+        if tracked.stuck_reported || open_for < stuck_after {
+            return None;
+        }
+        tracked.stuck_reported = true;
+        let message = format!("Circuit breaker {} has been open for {}s", name, open_for.as_secs());
+        Some(alert(message, name, tracked))
+    }
+}
+
+fn alert(message: String, name: &str, tracked: &Tracked) -> Alert {
+    let mut tags = BTreeMap::new();
+    tags.insert("breaker".into(), name.into());
+    for (label, value) in &tracked.labels {
+        tags.insert(format!("label.{}", label), value.clone());
+    }
+    if let Some(ref context) = tracked.last_failure {
+        for (key, value) in context.entries() {
+            tags.insert(format!("last_failure.{}", key), value.clone());
+        }
+    }
+    Alert { message, tags }
+}
+
+impl<B, A> Listener for SentryHook<B, A>
+where
+    B: Fn(&Record) + Send + Sync,
+    A: Fn(&Alert) + Send + Sync,
+{
+    fn on_transition(&self, transition: &Transition) {
+        (self.breadcrumb)(&logging::transition(transition));
+        let raised = {
+            let mut breakers = poison::lock(&self.breakers);
+            let tracked = breakers.entry(transition.name.clone()).or_default();
+            tracked.labels = transition.labels.clone();
+            match transition.to {
+                State::Open if tracked.opened_at.is_none() => {
+                    tracked.opened_at = Some(self.clock.now());
+                    tracked.stuck_reported = false;
+                    let message = format!("Circuit breaker {} opened ({:?})", transition.name, transition.reason);
+                    Some(alert(message, &transition.name, tracked))
+                }
+                State::Open => self.stuck(&transition.name, tracked),
+                State::HalfOpen => None,
+                State::Closed => {
+                    tracked.opened_at = None;
+                    None
+                }
+            }
+        };
+        if let Some(raised) = raised {
+            (self.alert)(&raised);
+        }
+    }
+
+    fn on_call(&self, call: &CallEvent) {
+        if call.outcome.is_failure() {
+            let mut breakers = poison::lock(&self.breakers);
+            breakers.entry(call.name.clone()).or_default().last_failure = Some(call.context.clone());
+            return;
+        }
+        if call.outcome != Outcome::Rejected {
+            return;
+        }
+        let raised = {
+            let mut breakers = poison::lock(&self.breakers);
+            breakers
+                .get_mut(&call.name)
+                .and_then(|tracked| self.stuck(&call.name, tracked))
+        };
+        if let Some(raised) = raised {
+            (self.alert)(&raised);
+        }
+    }
+
+    fn on_audit(&self, name: &str, entry: &AuditEntry) {
+        (self.breadcrumb)(&logging::audit(name, entry));
+    }
+}
@@ -0,0 +1,59 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static THREAD: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Threads are spread over the shards round-robin as they first record, so each keeps hitting the same one.
+pub(crate) fn thread_index() -> usize {
+    THREAD.with(|thread| match thread.get() {
+        Some(index) => index,
+        None => {
+            let index = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+            thread.set(Some(index));
+            index
+        }
+    })
+}
+
+/// Padded to its own cache lines, so threads recording into neighbouring shards do not contend.
+#[repr(align(128))]
+#[derive(Default)]
+struct Shard {
+    successes: AtomicU64,
+    slow: AtomicU64,
+}
+
+/// Success counters striped across shards, added up when the breaker next takes its lock.
+pub(crate) struct Shards {
+    shards: Box<[Shard]>,
+}
+
+impl Shards {
+    pub(crate) fn new(shards: usize) -> Shards {
+        Shards {
+            shards: (0..shards).map(|_| Shard::default()).collect(),
+        }
+    }
+
+    pub(crate) fn add(&self, slow: bool) {
+        let shard = &self.shards[thread_index() % self.shards.len()];
+        shard.successes.fetch_add(1, Ordering::Relaxed);
+        if slow {
+            shard.slow.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Takes the successes and slow calls recorded since the last drain.
+    pub(crate) fn drain(&self) -> (u64, u64) {
+        self.shards.iter().fold((0, 0), |(successes, slow), shard| {
+            (
+                successes.saturating_add(shard.successes.swap(0, Ordering::Relaxed)),
+                slow.saturating_add(shard.slow.swap(0, Ordering::Relaxed)),
+            )
+        })
+    }
+}
@@ -0,0 +1,257 @@
+//! A deterministic, single-threaded executor for exercising whole pipelines, such as retries around a timeout
+//! around a breaker, in CI. Time only moves when every task is waiting on a timer, randomness comes from the
+//! seed, and which ready task runs next is drawn from the seed as well, so a failing run is reproduced by
+//! running the same seed again.
+//!
+//! Breakers take part by using the simulation's clock and a seeded `rng`, and groups and outlier detectors by
+//! being built `with_clock`; maintenance windows, profile schedules and deadlines from `deadline_after` then
+//! follow simulated time too. Timeouts and backoff inside the simulation must use `Handle::sleep` and
+//! `Handle::timeout` rather than wall-clock timers, and breakers cannot wait for permits.
+
+use clock::ManualClock;
+use futures::future::{self, Either, Future, FutureExt};
+use futures::task::{self, ArcWake, Context, Poll, Waker};
+use poison;
+use rng::{Rng, XorShift};
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+struct Timer {
+    at: time::Duration,
+    seq: u64,
+    waker: Waker,
+}
+
+impl PartialEq for Timer {
+    fn eq(&self, other: &Timer) -> bool {
+        (self.at, self.seq) == (other.at, other.seq)
+    }
+}
+
+impl Eq for Timer {}
+
+impl PartialOrd for Timer {
+    fn partial_cmp(&self, other: &Timer) -> Option<::std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timer {
+    fn cmp(&self, other: &Timer) -> ::std::cmp::Ordering {
+        (self.at, self.seq).cmp(&(other.at, other.seq))
+    }
+}
+
+struct Woken {
+    id: usize,
+    woken: Arc<Mutex<Vec<usize>>>,
+}
+
+impl ArcWake for Woken {
+    fn wake_by_ref(arc_self: &Arc<Woken>) {
+        poison::lock(&arc_self.woken).push(arc_self.id);
+    }
+}
+
+struct Shared {
+    clock: Arc<ManualClock>,
+    rng: XorShift,
+    timers: BinaryHeap<Reverse<Timer>>,
+    next_timer: u64,
+    spawned: Vec<Task>,
+}
+
+/// A handle to the simulation for the tasks running in it.
+#[derive(Clone)]
+pub struct Handle {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Handle {
+    /// The simulation's clock, for `Options::clock`.
+    pub fn clock(&self) -> Arc<ManualClock> {
+        self.shared.borrow().clock.clone()
+    }
+
+    /// A generator seeded from the simulation, for `Options::rng`.
+    pub fn rng(&self) -> Box<dyn Rng> {
+        Box::new(XorShift::new(self.shared.borrow_mut().rng.next_u64()))
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        self.shared.borrow_mut().rng.next_u64()
+    }
+
+    /// How much simulated time has passed.
+    pub fn elapsed(&self) -> time::Duration {
+        self.shared.borrow().clock.elapsed()
+    }
+
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, task: F) {
+        self.shared.borrow_mut().spawned.push(Box::pin(task));
+    }
+
+    /// Completes once `duration` of simulated time has passed.
+    pub fn sleep(&self, duration: time::Duration) -> Sleep {
+        Sleep {
+            shared: self.shared.clone(),
+            at: self.elapsed() + duration,
+            registered: false,
+        }
+    }
+
+    /// Runs `task` until it completes or `duration` of simulated time passes, whichever is first.
+    pub fn timeout<F: Future>(&self, duration: time::Duration, task: F) -> impl Future<Output = Option<F::Output>> {
+        future::select(Box::pin(task), self.sleep(duration)).map(|either| match either {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        })
+    }
+}
+
+/// A timer on the simulation's clock.
+pub struct Sleep {
+    shared: Rc<RefCell<Shared>>,
+    at: time::Duration,
+    registered: bool,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let mut shared = this.shared.borrow_mut();
+        if shared.clock.elapsed() >= this.at {
+            return Poll::Ready(());
+        }
+        // Every poll comes from the task that owns the timer, so its first waker stays valid.
+        if !this.registered {
+            this.registered = true;
+            shared.next_timer += 1;
+            let timer = Timer {
+                at: this.at,
+                seq: shared.next_timer,
+                waker: cx.waker().clone(),
+            };
+            shared.timers.push(Reverse(timer));
+        }
+        Poll::Pending
+    }
+}
+
+/// How a simulation run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finished {
+    /// How many times a task was polled.
+    pub polls: u64,
+    /// How much simulated time passed.
+    pub elapsed: time::Duration,
+    /// Tasks left waiting on something other than a timer, which can never complete.
+    pub stalled: usize,
+}
+
+pub struct Simulation {
+    seed: u64,
+    handle: Handle,
+    tasks: Vec<Option<Task>>,
+    woken: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Simulation {
+    pub fn new(seed: u64) -> Simulation {
+        Simulation {
+            seed,
+            handle: Handle {
+                shared: Rc::new(RefCell::new(Shared {
+                    clock: Arc::new(ManualClock::new()),
+                    rng: XorShift::new(seed),
+                    timers: BinaryHeap::new(),
+                    next_timer: 0,
+                    spawned: Vec::new(),
+                })),
+            },
+            tasks: Vec::new(),
+            woken: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn handle(&self) -> Handle {
+        self.handle.clone()
+    }
+
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, task: F) {
+        self.handle.spawn(task);
+    }
+
+    fn adopt_spawned(&mut self) {
+        let spawned: Vec<Task> = self.handle.shared.borrow_mut().spawned.drain(..).collect();
+        for task in spawned {
+            poison::lock(&self.woken).push(self.tasks.len());
+            self.tasks.push(Some(task));
+        }
+    }
+
+    /// Wakes the timers that are due next, moving the clock forward to them. Returns false if there are none.
+    fn advance(&mut self) -> bool {
+        let mut shared = self.handle.shared.borrow_mut();
+        let at = match shared.timers.peek() {
+            Some(Reverse(timer)) => timer.at,
+            None => return false,
+        };
+        shared.clock.set(at);
+        while shared.timers.peek().is_some_and(|Reverse(timer)| timer.at <= at) {
+            if let Some(Reverse(timer)) = shared.timers.pop() {
+                timer.waker.wake();
+            }
+        }
+        true
+    }
+
+    /// Runs every task to completion, or until the remaining ones can make no progress.
+    pub fn run(&mut self) -> Finished {
+        let mut polls = 0;
+        loop {
+            self.adopt_spawned();
+            let mut ready: Vec<usize> = poison::lock(&self.woken).drain(..).collect();
+            ready.sort_unstable();
+            ready.dedup();
+            ready.retain(|&id| self.tasks[id].is_some());
+            if ready.is_empty() {
+                if self.advance() {
+                    continue;
+                }
+                break;
+            }
+            let pick = (self.handle.next_u64() % ready.len() as u64) as usize;
+            let id = ready.swap_remove(pick);
+            poison::lock(&self.woken).extend(ready);
+            if let Some(mut task) = self.tasks[id].take() {
+                let waker = task::waker(Arc::new(Woken {
+                    id,
+                    woken: self.woken.clone(),
+                }));
+                polls += 1;
+                if task.as_mut().poll(&mut Context::from_waker(&waker)).is_pending() {
+                    self.tasks[id] = Some(task);
+                }
+            }
+        }
+        Finished {
+            polls,
+            elapsed: self.handle.elapsed(),
+            stalled: self.tasks.iter().filter(|task| task.is_some()).count(),
+        }
+    }
+}
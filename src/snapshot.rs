@@ -0,0 +1,18 @@
+use flapping::Dampening;
+use interpact::{Counts, State};
+use std::collections::BTreeMap;
+
+/// A point-in-time view of a breaker, for health endpoints and periodic reporting.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub state: State,
+    /// Bumped on every state change and every new Closed interval, so data tagged with an older generation
+    /// describes a window that has already been left behind.
+    pub generation: u64,
+    pub counts: Counts,
+    pub dampening: Dampening,
+    pub profile: String,
+}
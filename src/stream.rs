@@ -0,0 +1,107 @@
+use errors;
+use futures::stream::Stream;
+use futures::task::{Context, Poll};
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnRejection {
+    /// Yields the rejection as an item without polling the stream, so a consumer that keeps pulling spins
+    /// until the breaker lets calls through again; it should back off on `Err`.
+    Yield,
+    Terminate,
+}
+
+pin_project! {
+    /// An item admitted but not yet received when the stream is dropped or ends counts as cancelled.
+    pub struct Protected<S, B>
+    where
+        B: Deref<Target = CircuitBreaker>,
+    {
+        #[pin]
+        stream: S,
+        cb: B,
+        on_rejection: OnRejection,
+        admitted: Option<(u64, time::Instant)>,
+        done: bool,
+    }
+
+    impl<S, B> PinnedDrop for Protected<S, B>
+    where
+        B: Deref<Target = CircuitBreaker>,
+    {
+        fn drop(this: Pin<&mut Self>) {
+            let this = this.project();
+            if let Some((generation, started)) = this.admitted.take() {
+                this.cb.after_call(generation, Outcome::Cancelled, started);
+            }
+        }
+    }
+}
+
+impl<S, B, T, E> Stream for Protected<S, B>
+where
+    S: Stream<Item = Result<T, E>>,
+    B: Deref<Target = CircuitBreaker>,
+{
+    type Item = Result<Result<T, E>, errors::CircuitBreakerError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+        let (generation, started) = match *this.admitted {
+            Some(admitted) => admitted,
+            None => match this.cb.before_request() {
+                Ok(generation) => {
+                    let admitted = (generation, this.cb.now());
+                    *this.admitted = Some(admitted);
+                    admitted
+                }
+                Err(err) => {
+                    if *this.on_rejection == OnRejection::Terminate {
+                        *this.done = true;
+                        return Poll::Ready(None);
+                    }
+                    return Poll::Ready(Some(Err(err)));
+                }
+            },
+        };
+        match this.stream.poll_next(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(None) => {
+                *this.admitted = None;
+                *this.done = true;
+                this.cb.after_call(generation, Outcome::Cancelled, started);
+                Poll::Ready(None)
+            }
+            Poll::Ready(Some(item)) => {
+                *this.admitted = None;
+                this.cb.after_call(generation, Outcome::from_success(item.is_ok()), started);
+                Poll::Ready(Some(Ok(item)))
+            }
+        }
+    }
+}
+
+/// Runs every item of a fallible stream through a breaker: admission before pulling an item, outcome once it arrives.
+pub trait StreamExt: Stream + Sized {
+    fn protect<B>(self, cb: B, on_rejection: OnRejection) -> Protected<Self, B>
+    where
+        B: Deref<Target = CircuitBreaker>,
+    {
+        Protected {
+            stream: self,
+            cb,
+            on_rejection,
+            admitted: None,
+            done: false,
+        }
+    }
+}
+
+impl<S: Stream> StreamExt for S {}
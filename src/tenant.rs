@@ -0,0 +1,25 @@
+use errors;
+use interpact::CircuitBreaker;
+use outcome::Outcome;
+use permit::Permit;
+use priority::Priority;
+use recording::Recording;
+
+impl CircuitBreaker {
+    /// Runs `task` on behalf of `tenant`. While HalfOpen, each tenant may use at most
+    /// `Options::max_requests_per_tenant` of the probe requests.
+    pub fn execute_for_tenant<T, E, F>(&self, tenant: &str, task: F) -> Result<Result<T, E>, errors::CircuitBreakerError>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = Permit::admitted(self, self.before_request_with(Priority::Normal, Some(tenant))?);
+        let task_result = task();
+        permit.record(Outcome::from_success(task_result.is_ok()));
+        Ok(task_result)
+    }
+
+    pub fn recording_for_tenant(&self, tenant: &str) -> Result<Recording<'_>, errors::CircuitBreakerError> {
+        let generation = self.before_request_with(Priority::Normal, Some(tenant))?;
+        Ok(Recording::new(self, generation))
+    }
+}
@@ -0,0 +1,201 @@
+//! Generators and invariant checks for property-testing the state machine, from this crate or from code
+//! that configures breakers. The generators in `strategies` draw from an `Rng` seeded by the caller, so a
+//! failing seed can be replayed:
+//!
+//! ```no_run
+//! # extern crate interpact;
+//! use interpact::testing::{self, strategies};
+//! use interpact::XorShift;
+//!
+//! # fn main() {
+//! for seed in 1..500 {
+//!     let mut rng = XorShift::new(seed);
+//!     let options = strategies::options(&mut rng);
+//!     let steps = strategies::steps(&mut rng, 200);
+//!     if let Err(violation) = testing::check(options, &steps) {
+//!         panic!("seed {}: {}", seed, violation);
+//!     }
+//! }
+//! # }
+//! ```
+
+use clock::ManualClock;
+use context::Context;
+use interpact::{CircuitBreaker, Options, State, Transition};
+use outcome::Outcome;
+use poison;
+use std::error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+/// Advance the clock by `after`, then attempt a call that ends with `outcome` after `latency`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub after: time::Duration,
+    pub outcome: Outcome,
+    pub latency: time::Duration,
+}
+
+/// A broken invariant, with the index of the step that broke it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub step: usize,
+    pub message: String,
+}
+
+impl error::Error for Violation {}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "step {}: {}", self.step, self.message)
+    }
+}
+
+/// Generators for breaker configurations and call sequences, drawing from an `Rng`.
+pub mod strategies {
+    use super::Step;
+    use interpact::{Options, TripPolicy};
+    use outcome::Outcome;
+    use rng::Rng;
+    use std::time;
+
+    fn below(rng: &mut dyn Rng, n: u64) -> u64 {
+        rng.next_u64() % n
+    }
+
+    /// Options with a random trip policy, probe limit, window and open timeout.
+    pub fn options(rng: &mut dyn Rng) -> Options<'static> {
+        let max_requests = 1 + below(rng, 4) as u32;
+        let trip_policy = if below(rng, 2) == 0 {
+            TripPolicy::ConsecutiveFailures(1 + below(rng, 10))
+        } else {
+            TripPolicy::FailureRate {
+                rate: 0.1 + 0.8 * rng.next_f64(),
+                min_requests: below(rng, 20),
+            }
+        };
+        Options {
+            name: "property",
+            max_requests,
+            success_threshold: Some(1 + below(rng, u64::from(max_requests)) as u32),
+            interval: time::Duration::from_millis(below(rng, 3) * below(rng, 60_000)),
+            timeout: time::Duration::from_millis(1 + below(rng, 30_000)),
+            trip_policy: Some(trip_policy),
+            ..Options::default()
+        }
+    }
+
+    /// A random outcome, failing with probability `failure_rate`. Successes are sometimes ignored or cancelled.
+    pub fn outcome(rng: &mut dyn Rng, failure_rate: f64) -> Outcome {
+        if rng.next_f64() < failure_rate {
+            return if below(rng, 4) == 0 {
+                Outcome::WeightedFailure(0.5 + 2.0 * rng.next_f64())
+            } else {
+                Outcome::Failure
+            };
+        }
+        match below(rng, 20) {
+            0 => Outcome::Ignored,
+            1 => Outcome::Cancelled,
+            _ => Outcome::Success,
+        }
+    }
+
+    /// A clock schedule: mostly short gaps between calls, with the occasional pause of up to a minute that
+    /// outlasts open timeouts and windows.
+    pub fn schedule(rng: &mut dyn Rng, len: usize) -> Vec<time::Duration> {
+        (0..len)
+            .map(|_| match below(rng, 10) {
+                0 => time::Duration::from_millis(below(rng, 60_000)),
+                _ => time::Duration::from_millis(below(rng, 100)),
+            })
+            .collect()
+    }
+
+    /// `len` steps on a random schedule, in bursts of failures and calm stretches.
+    pub fn steps(rng: &mut dyn Rng, len: usize) -> Vec<Step> {
+        let mut failure_rate = 0.0;
+        schedule(rng, len)
+            .into_iter()
+            .map(|after| {
+                if below(rng, 25) == 0 {
+                    failure_rate = rng.next_f64();
+                }
+                Step {
+                    after,
+                    outcome: outcome(rng, failure_rate),
+                    latency: time::Duration::from_millis(below(rng, 500)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Runs `steps` through a breaker built from `options` on a manual clock, checking after every step that
+///
+/// - no call is admitted while the breaker is Open,
+/// - no more than `max_requests` probes, raised by any dampening, are counted while HalfOpen,
+/// - successes and failures never outnumber the admitted calls,
+/// - every transition starts from the state the previous one ended in, and never goes from Closed straight
+///   to HalfOpen.
+///
+/// `options.clock` is replaced.
+pub fn check(mut options: Options, steps: &[Step]) -> Result<(), Violation> {
+    let clock = Arc::new(ManualClock::new());
+    options.clock = Some(clock.clone());
+    let cb = CircuitBreaker::new(options);
+    let transitions = Arc::new(Mutex::new(Vec::new()));
+    {
+        let transitions = transitions.clone();
+        let _ = cb.add_listener(move |transition: &Transition| poison::lock(&transitions).push(transition.clone()));
+    }
+    let context = Context::default();
+    let mut state = State::Closed;
+    let mut checked = 0;
+    for (i, step) in steps.iter().enumerate() {
+        let violation = |message: String| Violation { step: i, message };
+        clock.advance(step.after);
+        let before = cb.state();
+        match cb.before_request() {
+            Ok(_) if before == State::Open => return Err(violation("a call was admitted while Open".into())),
+            Ok(generation) => {
+                clock.advance(step.latency);
+                cb.after_call_took(generation, step.outcome, step.latency, &context);
+            }
+            Err(_) => {}
+        }
+        let counts = cb.counts();
+        let max_requests = u64::from(cb.dampening().max_requests);
+        if cb.state() == State::HalfOpen && counts.requests() > max_requests {
+            return Err(violation(format!(
+                "{} probes were counted while HalfOpen, more than max_requests ({})",
+                counts.requests(),
+                max_requests
+            )));
+        }
+        if counts.total_successes().saturating_add(counts.total_failures()) > counts.requests() {
+            return Err(violation(format!(
+                "{} successes and {} failures were counted for {} calls",
+                counts.total_successes(),
+                counts.total_failures(),
+                counts.requests()
+            )));
+        }
+        let transitions = poison::lock(&transitions);
+        for transition in &transitions[checked..] {
+            if transition.from != state {
+                return Err(violation(format!(
+                    "a transition started from {} while the breaker was {}",
+                    transition.from, state
+                )));
+            }
+            if transition.from == State::Closed && transition.to == State::HalfOpen {
+                return Err(violation("the breaker went from Closed straight to HalfOpen".into()));
+            }
+            state = transition.to;
+        }
+        checked = transitions.len();
+    }
+    Ok(())
+}
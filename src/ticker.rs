@@ -0,0 +1,102 @@
+use interpact::CircuitBreaker;
+use snapshot::Snapshot;
+use std::ops::Deref;
+use std::thread;
+use std::time;
+
+#[cfg(feature = "async")]
+use futures::stream::Stream;
+#[cfg(feature = "async")]
+use futures::task::{Context, Poll};
+#[cfg(feature = "async")]
+use futures_timer::Delay;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+
+/// Snapshots of a breaker taken every `interval`, for reporters that want a steady cadence. The iterator
+/// blocks the thread between snapshots. Ticks missed by a slow consumer are skipped rather than delivered in
+/// a burst.
+pub struct Snapshots<B> {
+    cb: B,
+    interval: time::Duration,
+    next: time::Instant,
+}
+
+impl<B: Deref<Target = CircuitBreaker>> Snapshots<B> {
+    pub fn new(cb: B, interval: time::Duration) -> Snapshots<B> {
+        Snapshots {
+            cb,
+            interval,
+            next: time::Instant::now() + interval,
+        }
+    }
+
+    fn tick(&mut self) -> Snapshot {
+        let now = time::Instant::now();
+        self.next += self.interval;
+        if self.next <= now {
+            self.next = now + self.interval;
+        }
+        self.cb.snapshot()
+    }
+}
+
+impl<B: Deref<Target = CircuitBreaker>> Iterator for Snapshots<B> {
+    type Item = Snapshot;
+
+    fn next(&mut self) -> Option<Snapshot> {
+        let wait = self.next.saturating_duration_since(time::Instant::now());
+        if wait > time::Duration::from_secs(0) {
+            thread::sleep(wait);
+        }
+        Some(self.tick())
+    }
+}
+
+/// Like `Snapshots`, as a stream that waits on a timer between snapshots.
+#[cfg(feature = "async")]
+pub struct SnapshotStream<B> {
+    snapshots: Snapshots<B>,
+    delay: Option<Delay>,
+}
+
+#[cfg(feature = "async")]
+impl<B: Deref<Target = CircuitBreaker>> SnapshotStream<B> {
+    pub fn new(cb: B, interval: time::Duration) -> SnapshotStream<B> {
+        SnapshotStream {
+            snapshots: Snapshots::new(cb, interval),
+            delay: None,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<B: Deref<Target = CircuitBreaker> + Unpin> Stream for SnapshotStream<B> {
+    type Item = Snapshot;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Snapshot>> {
+        let this = self.get_mut();
+        let next = this.snapshots.next;
+        let delay = this
+            .delay
+            .get_or_insert_with(|| Delay::new(next.saturating_duration_since(time::Instant::now())));
+        if Pin::new(delay).poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+        this.delay = None;
+        Poll::Ready(Some(this.snapshots.tick()))
+    }
+}
+
+impl CircuitBreaker {
+    pub fn snapshots(&self, interval: time::Duration) -> Snapshots<&CircuitBreaker> {
+        Snapshots::new(self, interval)
+    }
+
+    #[cfg(feature = "async")]
+    pub fn snapshot_stream(&self, interval: time::Duration) -> SnapshotStream<&CircuitBreaker> {
+        SnapshotStream::new(self, interval)
+    }
+}
@@ -0,0 +1,158 @@
+use flapping::Dampening;
+use interpact::State;
+use keyed::Keyed;
+use poison;
+use snapshot::Snapshot;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::panic;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time;
+
+/// A breaker that has been Open or flapping for longer than an escalation threshold. Handed to the escalation
+/// callback and to the breaker's listeners.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Escalation {
+    pub name: String,
+    pub state: State,
+    pub dampening: Dampening,
+    /// The threshold that was crossed.
+    pub after: time::Duration,
+    /// How long the breaker has been unhealthy, as far as the watchdog has seen.
+    pub unhealthy_for: time::Duration,
+}
+
+type Escalate = fn(escalation: &Escalation);
+
+struct Episode {
+    since: time::Instant,
+    escalated: usize,
+}
+
+/// Watches a `Keyed` set for breakers that stay unhealthy, meaning not Closed or dampened for flapping, and
+/// escalates each one once per threshold it crosses, e.g. a log line after a minute and a page after ten. An
+/// episode ends once the breaker is seen Closed and undampened again. Episodes are timed with each breaker's
+/// clock. Breakers are only seen when `check` runs, so the durations are as precise as the interval between
+/// checks.
+pub struct Watchdog<K> {
+    breakers: Arc<Keyed<K>>,
+    escalations: Vec<(time::Duration, Escalate)>,
+    episodes: Mutex<HashMap<K, Episode>>,
+}
+
+fn is_unhealthy(snapshot: &Snapshot) -> bool {
+    snapshot.state != State::Closed || snapshot.dampening.level > 0
+}
+
+impl<K: Hash + Eq + Clone + Send + Sync + 'static> Watchdog<K> {
+    pub fn new(breakers: Arc<Keyed<K>>) -> Watchdog<K> {
+        Watchdog {
+            breakers,
+            escalations: Vec::new(),
+            episodes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Calls `escalate` for every breaker that has been unhealthy for `after`.
+    pub fn escalate(mut self, after: time::Duration, escalate: Escalate) -> Watchdog<K> {
+        self.escalations.push((after, escalate));
+        self.escalations.sort_by_key(|&(after, _)| after);
+        self
+    }
+
+    /// Looks at every breaker once and returns the escalations that fired. Each one is also passed to the
+    /// breaker's listeners.
+    pub fn check(&self) -> Vec<Escalation> {
+        let mut fired = Vec::new();
+        {
+            let mut episodes = poison::lock(&self.episodes);
+            let breakers = self.breakers.breakers();
+            episodes.retain(|key, _| breakers.iter().any(|(other, _)| other == key));
+            for (key, cb) in breakers {
+                let snapshot = cb.snapshot();
+                let now = cb.now();
+                if !is_unhealthy(&snapshot) {
+                    episodes.remove(&key);
+                    continue;
+                }
+                let episode = episodes.entry(key).or_insert(Episode {
+                    since: now,
+                    escalated: 0,
+                });
+                let unhealthy_for = now.duration_since(episode.since);
+                while let Some(&(after, escalate)) = self.escalations.get(episode.escalated) {
+                    if unhealthy_for < after {
+                        break;
+                    }
+                    episode.escalated += 1;
+                    fired.push((
+                        escalate,
+                        cb.clone(),
+                        Escalation {
+                            name: snapshot.name.clone(),
+                            state: snapshot.state,
+                            dampening: snapshot.dampening,
+                            after,
+                            unhealthy_for,
+                        },
+                    ));
+                }
+            }
+        }
+        fired
+            .into_iter()
+            .map(|(escalate, cb, escalation)| {
+                escalate(&escalation);
+                cb.listeners.notify_escalation(&cb, &escalation);
+                escalation
+            })
+            .collect()
+    }
+
+    /// Runs `check` every `interval` on a dedicated thread until the returned handle is dropped.
+    pub fn spawn(self, interval: time::Duration) -> Watching {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread = {
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("interpact-watchdog".into())
+                .spawn(move || {
+                    let (ref stopped, ref condvar) = *stop;
+                    let mut guard = poison::lock(stopped);
+                    while !*guard {
+                        guard = poison::wait_timeout(condvar, guard, interval);
+                        if !*guard {
+                            drop(guard);
+                            // A panicking escalation must not stop the watchdog.
+                            let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| self.check()));
+                            guard = poison::lock(stopped);
+                        }
+                    }
+                })
+                .expect("failed to spawn the watchdog thread")
+        };
+        Watching {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// A running `Watchdog`. Dropping it stops the watchdog thread.
+pub struct Watching {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for Watching {
+    fn drop(&mut self) {
+        let (ref stopped, ref condvar) = *self.stop;
+        *poison::lock(stopped) = true;
+        condvar.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! A stable, versioned serde representation of transitions and calls for shipping to Kafka, Vector or ELK.
+//! Serialized as JSON an event reads
+//!
+//! ```text
+//! {"version":1,"timestamp_ms":1700000000000,"type":"transition","breaker":"db","labels":{},
+//!  "from":"closed","to":"open","reason":"tripped","generation":4}
+//! ```
+//!
+//! Within a version fields are only ever added, so consumers should ignore fields they do not know. Renaming
+//! or removing a field, or changing its meaning, bumps `VERSION`.
+
+use interpact::{Reason, State, Transition};
+use listener::{CallEvent, Listener};
+use outcome::Outcome;
+use std::collections::BTreeMap;
+use std::time;
+
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u32,
+    /// When the event was shipped, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Transition {
+        breaker: String,
+        labels: BTreeMap<String, String>,
+        from: State,
+        to: State,
+        reason: Reason,
+        generation: u64,
+    },
+    Call {
+        breaker: String,
+        generation: u64,
+        /// One of `success`, `slow`, `failure`, `ignored`, `rejected` or `cancelled`.
+        outcome: String,
+        /// The weight of a weighted failure.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        weight: Option<f64>,
+        duration_ms: f64,
+        context: BTreeMap<String, String>,
+    },
+}
+
+fn now_ms() -> u64 {
+    time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+impl Envelope {
+    pub fn new(event: Event) -> Envelope {
+        Envelope {
+            version: VERSION,
+            timestamp_ms: now_ms(),
+            event,
+        }
+    }
+}
+
+impl<'a> From<&'a Transition> for Event {
+    fn from(transition: &'a Transition) -> Event {
+        Event::Transition {
+            breaker: transition.name.clone(),
+            labels: transition.labels.clone(),
+            from: transition.from,
+            to: transition.to,
+            reason: transition.reason,
+            generation: transition.generation,
+        }
+    }
+}
+
+impl<'a> From<&'a CallEvent> for Event {
+    fn from(call: &'a CallEvent) -> Event {
+        let (outcome, weight) = match call.outcome {
+            Outcome::Success => ("success", None),
+            Outcome::Slow => ("slow", None),
+            Outcome::Failure => ("failure", None),
+            Outcome::WeightedFailure(weight) => ("failure", Some(weight)),
+            Outcome::Ignored => ("ignored", None),
+            Outcome::Rejected => ("rejected", None),
+            Outcome::Cancelled => ("cancelled", None),
+        };
+        Event::Call {
+            breaker: call.name.clone(),
+            generation: call.generation,
+            outcome: outcome.into(),
+            weight,
+            duration_ms: call.duration.as_secs_f64() * 1000.0,
+            context: call.context.entries().clone(),
+        }
+    }
+}
+
+/// A listener that wraps every transition and call in an `Envelope` and hands it to `ship`, e.g. to serialize
+/// it onto a queue. With `transitions_only`, calls are left out.
+pub struct Shipper<F> {
+    ship: F,
+    calls: bool,
+}
+
+impl<F: Fn(Envelope) + Send + Sync> Shipper<F> {
+    pub fn new(ship: F) -> Shipper<F> {
+        Shipper { ship, calls: true }
+    }
+
+    pub fn transitions_only(mut self) -> Shipper<F> {
+        self.calls = false;
+        self
+    }
+}
+
+impl<F: Fn(Envelope) + Send + Sync> Listener for Shipper<F> {
+    fn on_transition(&self, transition: &Transition) {
+        (self.ship)(Envelope::new(transition.into()));
+    }
+
+    fn on_call(&self, call: &CallEvent) {
+        if self.calls {
+            (self.ship)(Envelope::new(call.into()));
+        }
+    }
+}
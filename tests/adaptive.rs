@@ -0,0 +1,42 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::adaptive::MIN_TIMEOUT;
+use interpact::AdaptiveTimeout;
+use std::time::Duration;
+
+#[test]
+fn instant_calls_do_not_lock_the_timeout_at_zero() {
+    let timeout = AdaptiveTimeout::new(0.99, 2.0, Duration::from_secs(0), Duration::from_secs(1));
+    for _ in 0..10 {
+        timeout.observe(Duration::from_secs(0));
+    }
+    assert_eq!(timeout.timeout(), MIN_TIMEOUT);
+    let err = AdaptiveTimeout::try_new(0.99, 2.0, Duration::from_secs(0), Duration::from_secs(1)).unwrap_err();
+    assert_eq!(err.field, "min");
+}
+
+#[test]
+fn the_percentile_follows_the_most_recent_samples() {
+    let timeout = AdaptiveTimeout::new(0.5, 1.0, MIN_TIMEOUT, Duration::from_secs(60)).with_max_samples(3);
+    for millis in [30, 10, 20].iter() {
+        timeout.observe(Duration::from_millis(*millis));
+    }
+    assert_eq!(timeout.timeout(), Duration::from_millis(20));
+    // Evicts 30 and 10, leaving 20, 40 and 50.
+    timeout.observe(Duration::from_millis(40));
+    timeout.observe(Duration::from_millis(50));
+    assert_eq!(timeout.timeout(), Duration::from_millis(40));
+}
+
+#[test]
+fn any_factor_yields_a_timeout_within_bounds() {
+    for &factor in [f64::NAN, -1.0, 1e300].iter() {
+        let timeout = AdaptiveTimeout::new(0.5, factor, MIN_TIMEOUT, Duration::from_secs(1));
+        timeout.observe(Duration::from_millis(10));
+        let value = timeout.timeout();
+        assert!(value >= MIN_TIMEOUT && value <= Duration::from_secs(1));
+    }
+    assert!(AdaptiveTimeout::try_new(0.5, 1e300, MIN_TIMEOUT, Duration::from_secs(1)).is_ok());
+}
@@ -0,0 +1,84 @@
+//! `execute` must not allocate on its own, whether the call succeeds, fails or is rejected.
+
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, SlowCallPercentile};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Only the test's own thread is counted, not the harness.
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    ALLOCATIONS.store(0, Ordering::SeqCst);
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.load(Ordering::SeqCst)
+}
+
+fn succeed() -> Result<(), ()> {
+    Ok(())
+}
+
+fn fail() -> Result<(), ()> {
+    Err(())
+}
+
+#[test]
+fn execute_does_not_allocate() {
+    let cb = CircuitBreaker::builder().name("alloc").consecutive_failures(3).build();
+    let _ = cb.execute(succeed);
+    let _ = cb.execute(fail);
+
+    assert_eq!(allocations(|| assert!(cb.execute(succeed).is_ok())), 0, "successful call");
+    assert_eq!(allocations(|| assert!(cb.execute(fail).is_ok())), 0, "failed call");
+    let _ = cb.execute(fail);
+    let _ = cb.execute(fail);
+    assert_eq!(allocations(|| assert!(cb.execute(succeed).is_err())), 0, "rejected call");
+}
+
+#[test]
+fn judging_slow_calls_by_percentile_does_not_allocate() {
+    let cb = CircuitBreaker::builder()
+        .name("alloc-percentile")
+        .consecutive_failures(3)
+        .configure(|options| {
+            options.slow_call_percentile = Some(SlowCallPercentile {
+                percentile: 0.99,
+                factor: 2.0,
+                window: Duration::from_secs(60),
+                min_samples: 1,
+            })
+        })
+        .build();
+    let _ = cb.execute(succeed);
+
+    assert_eq!(allocations(|| assert!(cb.execute(succeed).is_ok())), 0, "judged call");
+}
@@ -0,0 +1,26 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, ManualClock, State};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn a_due_buffer_is_handed_over_on_admission() {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::builder()
+        .name("buffered")
+        .consecutive_failures(3)
+        .buffer_outcomes(100, Duration::from_secs(1))
+        .configure(|options| options.clock = Some(clock.clone()))
+        .build();
+    for _ in 0..3 {
+        assert!(cb.execute(|| Err::<(), _>("down")).is_ok());
+    }
+    // The failures are still buffered, so the breaker has not tripped yet.
+    assert!(cb.permit().is_ok());
+    clock.advance(Duration::from_secs(1));
+    assert!(cb.permit().is_err());
+    assert_eq!(cb.state(), State::Open);
+}
@@ -0,0 +1,31 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, Group, Options, State, TripPolicy};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn configure_cannot_replace_the_chosen_trip_policy() {
+    let cb = CircuitBreaker::builder()
+        .name("builder")
+        .consecutive_failures(1)
+        .configure(|options| {
+            options.name = "renamed";
+            options.trip_policy = Some(TripPolicy::ConsecutiveFailures(100));
+        })
+        .build();
+    assert_eq!(cb.name(), "builder");
+    assert!(cb.execute(|| Err::<(), _>("down")).is_ok());
+    assert_eq!(cb.state(), State::Open);
+}
+
+#[test]
+fn a_group_without_a_failure_budget_is_invalid() {
+    let options = Options {
+        group: Some(Arc::new(Group::new("siblings", 0, Duration::from_secs(60)))),
+        ..Options::default()
+    };
+    assert_eq!(options.validate().unwrap_err().field, "group");
+}
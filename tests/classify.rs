@@ -0,0 +1,19 @@
+extern crate interpact;
+
+use interpact::{Classifier, IoErrorClassifier, Outcome};
+use std::io;
+
+fn classify(kind: io::ErrorKind) -> Outcome {
+    IoErrorClassifier.classify(&Err::<(), _>(io::Error::from(kind)))
+}
+
+#[test]
+fn only_connection_errors_are_failures() {
+    assert_eq!(classify(io::ErrorKind::ConnectionRefused), Outcome::Failure);
+    assert_eq!(classify(io::ErrorKind::TimedOut), Outcome::Failure);
+    assert_eq!(classify(io::ErrorKind::BrokenPipe), Outcome::Failure);
+    assert_eq!(classify(io::ErrorKind::NotFound), Outcome::Ignored);
+    assert_eq!(classify(io::ErrorKind::InvalidInput), Outcome::Ignored);
+    assert_eq!(classify(io::ErrorKind::PermissionDenied), Outcome::Ignored);
+    assert_eq!(classify(io::ErrorKind::WouldBlock), Outcome::Ignored);
+}
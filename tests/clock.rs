@@ -0,0 +1,62 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, Clock, Maintenance, MaintenanceWindow, ManualClock, Options, State, TripPolicy};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn maintenance_windows_follow_the_breaker_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::new(Options {
+        name: "maintenance",
+        clock: Some(clock.clone()),
+        ..Options::default()
+    });
+    cb.add_maintenance_window(MaintenanceWindow {
+        start: clock.wall() + Duration::from_secs(3600),
+        duration: Duration::from_secs(60),
+        every: None,
+        mode: Maintenance::ForceOpen,
+    });
+    assert!(cb.permit().is_ok());
+    clock.advance(Duration::from_secs(3600));
+    assert_eq!(cb.maintenance(), Some(Maintenance::ForceOpen));
+    assert!(cb.permit().is_err());
+    clock.advance(Duration::from_secs(60));
+    assert!(cb.permit().is_ok());
+}
+
+#[test]
+fn waiting_for_permits_needs_a_clock_that_advances() {
+    let options = Options {
+        name: "waiting",
+        max_waiting: 1,
+        max_wait: Duration::from_secs(1),
+        clock: Some(Arc::new(ManualClock::new())),
+        ..Options::default()
+    };
+    assert_eq!(options.validate().unwrap_err().field, "max_waiting");
+}
+
+#[test]
+fn timeout_jitter_is_kept_between_zero_and_one() {
+    for &jitter in [f64::NAN, -2.0, 1e300].iter() {
+        let clock = Arc::new(ManualClock::new());
+        let options = Options {
+            name: "jitter",
+            timeout: Duration::from_secs(1),
+            timeout_jitter: jitter,
+            trip_policy: Some(TripPolicy::ConsecutiveFailures(1)),
+            clock: Some(clock.clone()),
+            ..Options::default()
+        };
+        assert_eq!(options.validate().unwrap_err().field, "timeout_jitter");
+        let cb = CircuitBreaker::new(options);
+        assert!(cb.execute(|| Err::<(), ()>(())).is_ok());
+        assert_eq!(cb.state(), State::Open);
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(cb.state(), State::HalfOpen);
+    }
+}
@@ -0,0 +1,27 @@
+extern crate interpact;
+
+use interpact::compat;
+
+#[test]
+fn resilience4j_defaults_apply_to_unset_properties() {
+    let config = compat::resilience4j(&[("slidingWindowType", "TIME_BASED"), ("slidingWindowSize", "30")]).unwrap();
+    assert_eq!(config.interval_ms, Some(30 * 1000));
+    assert_eq!(config.failure_rate, Some(0.5));
+    assert_eq!(config.min_requests, Some(100));
+    assert_eq!(config.max_requests, Some(10));
+}
+
+#[test]
+fn resilience4j_windows_are_count_based_unless_stated() {
+    let err = compat::resilience4j(&[("slidingWindowSize", "30")]).unwrap_err();
+    assert_eq!(err.field, "interval");
+}
+
+#[test]
+fn hystrix_defaults_apply_to_unset_properties() {
+    let config = compat::hystrix(&[("hystrix.command.default.circuitBreaker.errorThresholdPercentage", "25")]).unwrap();
+    assert_eq!(config.failure_rate, Some(0.25));
+    assert_eq!(config.min_requests, Some(20));
+    assert_eq!(config.timeout_ms, Some(5000));
+    assert_eq!(config.interval_ms, Some(10 * 1000));
+}
@@ -0,0 +1,395 @@
+//! `Config::json_schema` is written by hand, so these tests keep it in step with `Config`: every serialized field
+//! must be a schema property and every property a field, and configurations must survive a serde round trip.
+
+#![cfg(feature = "serde")]
+
+extern crate interpact;
+extern crate serde;
+
+use interpact::{CancelledAs, Config, Options};
+use serde::de::{self, IntoDeserializer};
+use serde::ser::{self, Impossible, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Null,
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Str(String),
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error(msg.to_string())
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T, Error> {
+    Err(Error(format!("{} is not supported", what)))
+}
+
+/// Serializes a flat struct of scalars into its fields, in order.
+struct ToValue;
+
+impl ser::Serializer for ToValue {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = Impossible<Value, Error>;
+    type SerializeTuple = Impossible<Value, Error>;
+    type SerializeTupleStruct = Impossible<Value, Error>;
+    type SerializeTupleVariant = Impossible<Value, Error>;
+    type SerializeMap = Impossible<Value, Error>;
+    type SerializeStruct = Impossible<Value, Error>;
+    type SerializeStructVariant = Impossible<Value, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(Value::Signed(v.into()))
+    }
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(Value::Signed(v.into()))
+    }
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(Value::Signed(v.into()))
+    }
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::Signed(v))
+    }
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(Value::Unsigned(v.into()))
+    }
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(Value::Unsigned(v.into()))
+    }
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(Value::Unsigned(v.into()))
+    }
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::Unsigned(v))
+    }
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(Value::Float(v.into()))
+    }
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(Value::Float(v))
+    }
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::Str(v.to_string()))
+    }
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::Str(v.into()))
+    }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Value, Error> {
+        unsupported("bytes")
+    }
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Value, Error> {
+        Ok(Value::Str(variant.into()))
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value, Error> {
+        unsupported("a newtype variant")
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        unsupported("a sequence")
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        unsupported("a tuple")
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        unsupported("a tuple struct")
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        unsupported("a tuple variant")
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        unsupported("a map")
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        unsupported("a nested struct")
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        unsupported("a struct variant")
+    }
+}
+
+struct Fields(Vec<(String, Value)>);
+
+impl ser::SerializeStruct for Fields {
+    type Ok = Vec<(String, Value)>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.0.push((key.into(), value.serialize(ToValue)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Vec<(String, Value)>, Error> {
+        Ok(self.0)
+    }
+}
+
+/// Like `ToValue`, but only for the top-level struct.
+struct ToFields;
+
+macro_rules! not_a_struct {
+    ($($method:ident($($arg:ty),*)),*) => {
+        $(fn $method(self, $(_: $arg),*) -> Result<Vec<(String, Value)>, Error> {
+            unsupported("anything but a struct")
+        })*
+    };
+}
+
+impl ser::Serializer for ToFields {
+    type Ok = Vec<(String, Value)>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = Impossible<Self::Ok, Error>;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<Self::Ok, Error>;
+
+    not_a_struct!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str)
+    );
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<Self::Ok, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, _value: &T) -> Result<Self::Ok, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        unsupported("anything but a struct")
+    }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Fields, Error> {
+        Ok(Fields(Vec::with_capacity(len)))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        unsupported("anything but a struct")
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::Unsigned(v) => visitor.visit_u64(v),
+            Value::Signed(v) => visitor.visit_i64(v),
+            Value::Float(v) => visitor.visit_f64(v),
+            Value::Str(v) => visitor.visit_string(v),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            Value::Str(variant) => visitor.visit_enum(variant.into_deserializer()),
+            value => Err(Error(format!("expected a variant name, found {:?}", value))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+fn round_trip(config: &Config) -> Config {
+    let fields = config.serialize(ToFields).unwrap();
+    let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, properties(), "Config's fields and the schema's properties differ");
+    let deserializer: de::value::MapDeserializer<_, Error> = de::value::MapDeserializer::new(fields.into_iter());
+    serde::Deserialize::deserialize(deserializer).unwrap()
+}
+
+/// The property names of the schema, in order. Each property is on its own line.
+fn properties() -> Vec<&'static str> {
+    let schema = Config::json_schema();
+    let start = schema.find("\"properties\": {").expect("the schema has properties");
+    schema[start..]
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let line = line.trim();
+            let name = line.strip_prefix('"')?;
+            let end = name.find("\": {")?;
+            Some(&name[..end])
+        })
+        .collect()
+}
+
+#[test]
+fn default_config_round_trips() {
+    assert_eq!(round_trip(&Config::default()), Config::default());
+}
+
+#[test]
+fn full_config_round_trips() {
+    let config = Config {
+        max_requests: Some(3),
+        success_threshold: Some(2),
+        interval_ms: Some(60_000),
+        timeout_ms: Some(30_000),
+        timeout_jitter: Some(0.2),
+        min_open_duration_ms: Some(1_000),
+        max_half_open_duration_ms: Some(10_000),
+        fast_window_ms: Some(5_000),
+        slow_call_duration_ms: Some(2_000),
+        idle_reset_ms: Some(600_000),
+        max_waiting: Some(4),
+        max_wait_ms: Some(250),
+        history: Some(16),
+        cancelled_as: Some(CancelledAs::Failure),
+        consecutive_failures: Some(5),
+        failure_rate: Some(0.5),
+        min_requests: Some(20),
+    };
+    assert_eq!(round_trip(&config), config);
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let fields = vec![(String::from("max_request"), Value::Unsigned(1))];
+    let deserializer: de::value::MapDeserializer<_, Error> = de::value::MapDeserializer::new(fields.into_iter());
+    let config: Result<Config, Error> = serde::Deserialize::deserialize(deserializer);
+    assert!(config.is_err());
+}
+
+#[test]
+fn validation_agrees_with_the_schema_minimum_for_consecutive_failures() {
+    let mut options = Options::default();
+    Config {
+        consecutive_failures: Some(0),
+        ..Config::default()
+    }
+    .apply(&mut options);
+    assert_eq!(options.validate().unwrap_err().field, "trip_policy");
+}
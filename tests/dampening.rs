@@ -0,0 +1,37 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, ManualClock, Options, Outcome, State, TripPolicy};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn dampened_breaker_closes_again() {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::new(Options {
+        name: "flapping",
+        max_requests: 2,
+        timeout: Duration::from_secs(1),
+        trip_policy: Some(TripPolicy::ConsecutiveFailures(1)),
+        flap_window: Duration::from_secs(60),
+        flap_threshold: 2,
+        clock: Some(clock.clone()),
+        ..Options::default()
+    });
+    // Trip, fail the probe and trip again, which dampens the breaker.
+    cb.permit().unwrap().failure();
+    clock.advance(Duration::from_secs(1));
+    cb.permit().unwrap().failure();
+    let dampening = cb.dampening();
+    assert_eq!(dampening.level, 1);
+    assert_eq!(dampening.success_threshold, 4);
+    assert_eq!(dampening.max_requests, 4);
+
+    clock.advance(dampening.timeout);
+    assert_eq!(cb.state(), State::HalfOpen);
+    for _ in 0..dampening.success_threshold {
+        cb.permit().unwrap().record(Outcome::Success);
+    }
+    assert_eq!(cb.state(), State::Closed);
+}
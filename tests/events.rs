@@ -0,0 +1,19 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::events::{Dispatched, Overflow};
+use interpact::{CircuitBreaker, State};
+
+#[test]
+fn a_full_queue_drops_transitions_without_waiting() {
+    let cb = CircuitBreaker::builder().name("events").consecutive_failures(1).build();
+    let (dispatched, events) = Dispatched::new(1, Overflow::DropNewest);
+    let _ = cb.add_listener(dispatched.clone());
+    cb.force_open(None);
+    cb.force_closed(None);
+    cb.force_open(None);
+    assert_eq!(dispatched.dropped(), 2);
+    assert_eq!(events.try_recv().map(|transition| transition.to), Some(State::Open));
+    assert!(events.try_recv().is_none());
+}
@@ -0,0 +1,32 @@
+#![cfg(all(feature = "ffi", not(feature = "noop")))]
+
+extern crate interpact;
+
+use interpact::ffi::*;
+use std::ffi::CString;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn admission_applies_the_half_open_probe_limit() {
+    let name = CString::new("ffi").unwrap();
+    unsafe {
+        let breaker = interpact_breaker_new(name.as_ptr(), 1, 0, 1, 1);
+        interpact_breaker_record_failure(breaker, interpact_breaker_admit(breaker));
+        assert_eq!(interpact_breaker_state(breaker), InterpactState::Open);
+        thread::sleep(Duration::from_millis(5));
+        assert_eq!(interpact_breaker_state(breaker), InterpactState::HalfOpen);
+
+        let probe = interpact_breaker_admit(breaker);
+        assert!(!probe.is_null());
+        assert!(interpact_breaker_admit(breaker).is_null());
+        assert!(interpact_breaker_is_call_permitted(breaker));
+        interpact_breaker_release(breaker, probe);
+
+        let probe = interpact_breaker_admit(breaker);
+        assert!(!probe.is_null());
+        interpact_breaker_record_success(breaker, probe);
+        assert_eq!(interpact_breaker_state(breaker), InterpactState::Closed);
+        interpact_breaker_free(breaker);
+    }
+}
@@ -0,0 +1,39 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CallEvent, CircuitBreaker, Filtered, Listener, Options, Transition};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone, Default)]
+struct Seen {
+    transitions: Arc<AtomicUsize>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Listener for Seen {
+    fn on_transition(&self, _transition: &Transition) {
+        self.transitions.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_call(&self, _call: &CallEvent) {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn sampling_thins_calls_but_never_transitions() {
+    let cb = CircuitBreaker::new(Options::default());
+    let seen = Seen::default();
+    let _ = cb.add_listener(Filtered::new(seen.clone()).sample(0.5));
+    for _ in 0..10 {
+        assert!(cb.execute(|| Ok::<(), ()>(())).is_ok());
+    }
+    for _ in 0..3 {
+        cb.force_open(None);
+        cb.force_closed(None);
+    }
+    assert_eq!(seen.calls.load(Ordering::SeqCst), 5);
+    assert_eq!(seen.transitions.load(Ordering::SeqCst), 6);
+}
@@ -0,0 +1,41 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, ManualClock, Options, State, TripPolicy};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn half_open(max_requests: u32) -> (CircuitBreaker, Arc<ManualClock>) {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::new(Options {
+        name: "half-open",
+        max_requests,
+        timeout: Duration::from_secs(1),
+        trip_policy: Some(TripPolicy::ConsecutiveFailures(1)),
+        clock: Some(clock.clone()),
+        ..Options::default()
+    });
+    cb.permit().unwrap().failure();
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(cb.state(), State::HalfOpen);
+    (cb, clock)
+}
+
+#[test]
+fn max_requests_bounds_the_probes() {
+    let (cb, _clock) = half_open(1);
+    let probe = cb.permit().unwrap();
+    assert!(cb.permit().is_err());
+    probe.success();
+    assert_eq!(cb.state(), State::Closed);
+}
+
+#[test]
+fn a_cancelled_probe_gives_its_permit_back() {
+    let (cb, _clock) = half_open(1);
+    drop(cb.permit().unwrap());
+    assert_eq!(cb.state(), State::HalfOpen);
+    cb.permit().unwrap().success();
+    assert_eq!(cb.state(), State::Closed);
+}
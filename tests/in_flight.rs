@@ -0,0 +1,28 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{gobreaker, CircuitBreaker, Options};
+use std::panic;
+
+fn panics() -> Result<(), ()> {
+    panic!("task panicked")
+}
+
+#[test]
+fn a_panicking_task_leaves_nothing_in_flight() {
+    let cb = CircuitBreaker::new(Options {
+        max_in_flight: Some(1),
+        ..Options::default()
+    });
+    assert!(panic::catch_unwind(panic::AssertUnwindSafe(|| cb.execute(panics))).is_err());
+    assert_eq!(cb.in_flight(), 0);
+    assert!(cb.execute(|| Ok::<(), ()>(())).is_ok());
+}
+
+#[test]
+fn a_dropped_two_step_request_leaves_nothing_in_flight() {
+    let cb = gobreaker::new_two_step_circuit_breaker(gobreaker::Settings::default());
+    drop(cb.allow().ok());
+    assert_eq!(cb.inner().in_flight(), 0);
+}
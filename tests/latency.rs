@@ -0,0 +1,35 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, Options, SlowCallPercentile};
+use std::time::Duration;
+
+fn with_factor(factor: f64) -> Options<'static> {
+    Options {
+        name: "latency",
+        slow_call_percentile: Some(SlowCallPercentile {
+            percentile: 0.5,
+            factor,
+            window: Duration::from_secs(60),
+            min_samples: 1,
+        }),
+        ..Options::default()
+    }
+}
+
+#[test]
+fn slow_call_factors_must_be_positive_numbers() {
+    for &factor in [f64::NAN, -1.0, 0.0, f64::INFINITY].iter() {
+        assert_eq!(with_factor(factor).validate().unwrap_err().field, "slow_call_percentile");
+        let cb = CircuitBreaker::new(with_factor(factor));
+        for _ in 0..3 {
+            assert!(cb.execute(|| Ok::<(), ()>(())).is_ok());
+        }
+    }
+    let cb = CircuitBreaker::new(with_factor(1e300));
+    for _ in 0..3 {
+        assert!(cb.execute(|| Ok::<(), ()>(())).is_ok());
+    }
+    assert_eq!(cb.counts().slow_calls(), 0);
+}
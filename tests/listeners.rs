@@ -0,0 +1,48 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::producer::WhileOpen;
+use interpact::{gobreaker, CallEvent, CircuitBreaker, Composite, Listener, Options, Outcome, Producer, Transition};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone, Default)]
+struct Calls(Arc<Mutex<Vec<Outcome>>>);
+
+impl Listener for Calls {
+    fn on_transition(&self, _transition: &Transition) {}
+
+    fn on_call(&self, call: &CallEvent) {
+        self.0.lock().unwrap().push(call.outcome);
+    }
+}
+
+fn observed(cb: &CircuitBreaker) -> Calls {
+    let calls = Calls::default();
+    let _ = cb.add_listener(calls.clone());
+    calls
+}
+
+#[test]
+fn every_execution_path_reports_its_calls() {
+    let cb = Arc::new(CircuitBreaker::new(Options {
+        slow_call_duration: Duration::from_nanos(1),
+        ..Options::default()
+    }));
+    let calls = observed(&cb);
+
+    let composite = Composite::all(vec![cb.clone()]);
+    assert!(composite.execute(|| Err::<(), ()>(())).is_ok());
+    let mut producer = Producer::new(&*cb, WhileOpen::Error, |_: u32| {
+        std::thread::sleep(Duration::from_millis(1));
+        Ok::<(), ()>(())
+    });
+    assert!(producer.send(1).is_ok());
+    assert_eq!(*calls.0.lock().unwrap(), vec![Outcome::Failure, Outcome::Slow]);
+
+    let two_step = gobreaker::new_two_step_circuit_breaker(gobreaker::Settings::default());
+    let calls = observed(two_step.inner());
+    two_step.allow().ok().unwrap().done(true);
+    assert_eq!(*calls.0.lock().unwrap(), vec![Outcome::Success]);
+}
@@ -0,0 +1,29 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, ManualClock, Options, OutlierDetector, OutlierOptions};
+use std::sync::Arc;
+
+#[test]
+fn an_idle_endpoint_does_not_hide_outliers() {
+    let detector = OutlierDetector::new(
+        |_: &&str| CircuitBreaker::new(Options::default()),
+        OutlierOptions {
+            min_endpoints: 3,
+            min_requests: 0,
+            stdev_factor: 1.0,
+            max_ejection_percent: 100,
+            ..OutlierOptions::default()
+        },
+    )
+    .with_clock(Arc::new(ManualClock::new()));
+    detector.add("idle");
+    for key in ["a", "b"].iter() {
+        assert!(detector.execute(key, || Ok::<(), ()>(())).is_ok());
+    }
+    assert!(detector.execute(&"c", || Err::<(), ()>(())).is_ok());
+    detector.evaluate();
+    assert!(detector.is_ejected(&"c"));
+    assert!(!detector.is_ejected(&"a") && !detector.is_ejected(&"b") && !detector.is_ejected(&"idle"));
+}
@@ -0,0 +1,59 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::producer::Sent;
+use interpact::{CircuitBreaker, ManualClock, Options, Producer, TripPolicy};
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn a_buffered_item_that_fails_to_send_is_kept() {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::new(Options {
+        name: "producer",
+        timeout: Duration::from_secs(1),
+        trip_policy: Some(TripPolicy::ConsecutiveFailures(1)),
+        clock: Some(clock.clone()),
+        ..Options::default()
+    });
+    let sent = RefCell::new(Vec::new());
+    let failing = RefCell::new(true);
+    let mut producer = Producer::buffering(&cb, 10, |item: u32| {
+        if *failing.borrow() {
+            return Err(item);
+        }
+        sent.borrow_mut().push(item);
+        Ok(())
+    });
+    // The first send trips the breaker; the second is buffered while Open.
+    assert_eq!(producer.send(1).ok(), Some(Err(1)));
+    assert_eq!(producer.send(2).ok(), Some(Ok(Sent::Buffered)));
+
+    // The buffered item fails again once probing, so the new item is buffered behind it.
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(producer.send(3).ok(), Some(Ok(Sent::Buffered)));
+    assert_eq!(producer.buffered(), 2);
+
+    *failing.borrow_mut() = false;
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(producer.flush().ok(), Some(Ok(())));
+    assert_eq!(*sent.borrow(), vec![2, 3]);
+}
+
+#[test]
+fn items_arriving_at_a_full_buffer_are_dropped() {
+    let cb = CircuitBreaker::new(Options {
+        name: "producer",
+        timeout: Duration::from_secs(60),
+        trip_policy: Some(TripPolicy::ConsecutiveFailures(1)),
+        ..Options::default()
+    });
+    let mut producer = Producer::buffering(&cb, 1, |item: u32| Err(item));
+    assert_eq!(producer.send(1).ok(), Some(Err(1)));
+    assert_eq!(producer.send(2).ok(), Some(Ok(Sent::Buffered)));
+    assert_eq!(producer.send(3).ok(), Some(Ok(Sent::Dropped)));
+    assert_eq!(producer.buffered(), 1);
+    assert_eq!(producer.dropped(), 1);
+}
@@ -0,0 +1,34 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{AuditAction, CircuitBreaker, Profile};
+
+#[test]
+fn only_a_switch_that_happens_is_audited() {
+    let cb = CircuitBreaker::builder().name("profiles").consecutive_failures(5).build();
+    cb.add_profile(Profile {
+        name: String::from("strict"),
+        ..cb.profile()
+    });
+    assert!(!cb.switch_profile("missing"));
+    assert!(cb.switch_profile("strict"));
+    let actions: Vec<AuditAction> = cb.audit_log().into_iter().map(|entry| entry.action).collect();
+    assert_eq!(
+        actions,
+        vec![
+            AuditAction::AddProfile(String::from("strict")),
+            AuditAction::SwitchProfile(String::from("strict")),
+        ]
+    );
+}
+
+#[test]
+fn replacing_the_active_profile_applies_it() {
+    let cb = CircuitBreaker::builder().name("profiles").consecutive_failures(5).build();
+    cb.add_profile(Profile {
+        max_requests: 7,
+        ..cb.profile()
+    });
+    assert_eq!(cb.profile().max_requests, 7);
+}
@@ -0,0 +1,18 @@
+#![cfg(all(feature = "testing", not(feature = "noop")))]
+
+extern crate interpact;
+
+use interpact::testing::{self, strategies};
+use interpact::XorShift;
+
+#[test]
+fn invariants_hold_across_seeds() {
+    for seed in 1..500 {
+        let mut rng = XorShift::new(seed);
+        let options = strategies::options(&mut rng);
+        let steps = strategies::steps(&mut rng, 200);
+        if let Err(violation) = testing::check(options, &steps) {
+            panic!("seed {}: {}", seed, violation);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, ManualClock, Options, Outcome, Recorder};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn traces_keep_the_breaker_clock_and_unjudged_outcomes() {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::new(Options {
+        name: "replay",
+        slow_call_duration: Duration::from_millis(100),
+        clock: Some(clock.clone()),
+        ..Options::default()
+    });
+    let recorder = Recorder::for_breaker(&cb);
+    let _ = cb.add_listener(recorder.clone());
+
+    clock.advance(Duration::from_secs(5));
+    let permit = cb.permit().unwrap();
+    clock.advance(Duration::from_millis(200));
+    permit.success();
+    assert_eq!(cb.counts().slow_calls(), 1);
+
+    let trace = recorder.trace();
+    assert_eq!(trace.calls.len(), 1);
+    assert_eq!(trace.calls[0].at, Duration::from_millis(5200));
+    assert_eq!(trace.calls[0].outcome, Outcome::Success);
+    assert_eq!(trace.calls[0].latency, Duration::from_millis(200));
+}
@@ -0,0 +1,19 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, RetryBudget};
+
+#[test]
+fn a_rejected_retry_returns_the_last_error() {
+    let cb = CircuitBreaker::builder().name("retry").consecutive_failures(1).build();
+    let budget = RetryBudget::new(0.1, 10.0);
+    let mut attempts = 0;
+    let result = cb.execute_with_retry(&budget, 3, || {
+        attempts += 1;
+        Err::<(), _>("refused")
+    });
+    assert_eq!(result.ok(), Some(Err("refused")));
+    assert_eq!(attempts, 1);
+    assert!(cb.execute_with_retry(&budget, 3, || Ok::<(), ()>(())).is_err());
+}
@@ -0,0 +1,29 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, Keyed, Options};
+use std::panic;
+
+#[test]
+fn a_panicking_call_resumes_its_panic_and_gives_back_its_admission() {
+    let keyed = Keyed::new(|_: &&str| {
+        CircuitBreaker::new(Options {
+            max_in_flight: Some(1),
+            ..Options::default()
+        })
+    });
+    let caught = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        keyed.scatter_gather(vec!["a", "b"], |&key| {
+            if key == "b" {
+                panic!("endpoint b panicked");
+            }
+            Ok::<_, ()>(key)
+        })
+    }));
+    let payload = caught.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"endpoint b panicked"));
+    let gathered = keyed.scatter_gather(vec!["a", "b"], |&key| Ok::<_, ()>(key));
+    assert_eq!(gathered.responses.len(), 2);
+    assert!(gathered.skipped.is_empty());
+}
@@ -0,0 +1,43 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::logging::Record;
+use interpact::{Alert, CircuitBreaker, ManualClock, Options, SentryHook, State, TripPolicy};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn failing_probes_stay_in_one_episode_until_it_is_stuck() {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::new(Options {
+        name: "sentry",
+        timeout: Duration::from_secs(1),
+        trip_policy: Some(TripPolicy::ConsecutiveFailures(1)),
+        clock: Some(clock.clone()),
+        ..Options::default()
+    });
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let raised = alerts.clone();
+    let hook = SentryHook::new(
+        |_: &Record| {},
+        move |alert: &Alert| raised.lock().unwrap().push(alert.message.clone()),
+    )
+    .stuck_after(Duration::from_secs(3))
+    .with_clock(clock.clone());
+    let _ = cb.add_listener(hook);
+
+    cb.permit().unwrap().failure();
+    for _ in 0..4 {
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cb.state(), State::HalfOpen);
+        cb.permit().unwrap().failure();
+    }
+    assert_eq!(
+        *alerts.lock().unwrap(),
+        vec![
+            String::from("Circuit breaker sentry opened (Tripped)"),
+            String::from("Circuit breaker sentry has been open for 3s"),
+        ]
+    );
+}
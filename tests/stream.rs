@@ -0,0 +1,39 @@
+#![cfg(all(feature = "async", not(feature = "noop")))]
+
+extern crate futures;
+extern crate interpact;
+
+use futures::stream::{self, StreamExt as _};
+use futures::task::{noop_waker, Context};
+use interpact::stream::OnRejection;
+use interpact::{CircuitBreaker, ManualClock, Options, State, StreamExt, TripPolicy};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn half_open(max_requests: u32) -> (CircuitBreaker, Arc<ManualClock>) {
+    let clock = Arc::new(ManualClock::new());
+    let cb = CircuitBreaker::new(Options {
+        name: "half-open",
+        max_requests,
+        timeout: Duration::from_secs(1),
+        trip_policy: Some(TripPolicy::ConsecutiveFailures(1)),
+        clock: Some(clock.clone()),
+        ..Options::default()
+    });
+    cb.permit().unwrap().failure();
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(cb.state(), State::HalfOpen);
+    (cb, clock)
+}
+
+#[test]
+fn a_dropped_stream_gives_its_probe_back() {
+    let (cb, _clock) = half_open(1);
+    {
+        let mut items = stream::pending::<Result<(), ()>>().protect(&cb, OnRejection::Yield);
+        assert!(items.poll_next_unpin(&mut Context::from_waker(&noop_waker())).is_pending());
+        assert!(cb.permit().is_err());
+    }
+    cb.permit().unwrap().success();
+    assert_eq!(cb.state(), State::Closed);
+}
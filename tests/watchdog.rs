@@ -0,0 +1,44 @@
+#![cfg(not(feature = "noop"))]
+
+extern crate interpact;
+
+use interpact::{CircuitBreaker, Escalation, Keyed, Listener, ManualClock, Options, Transition, Watchdog};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+thread_local!(static CLOCK: Arc<ManualClock> = Arc::new(ManualClock::new()));
+
+fn breaker(_key: &&'static str) -> CircuitBreaker {
+    CLOCK.with(|clock| {
+        CircuitBreaker::new(Options {
+            clock: Some(clock.clone()),
+            ..Options::default()
+        })
+    })
+}
+
+#[derive(Clone, Default)]
+struct Escalations(Arc<Mutex<Vec<Duration>>>);
+
+impl Listener for Escalations {
+    fn on_transition(&self, _transition: &Transition) {}
+
+    fn on_escalation(&self, escalation: &Escalation) {
+        self.0.lock().unwrap().push(escalation.unhealthy_for);
+    }
+}
+
+#[test]
+fn escalations_are_timed_by_the_breaker_clock_and_reach_its_listeners() {
+    let breakers = Arc::new(Keyed::new(breaker));
+    let cb = breakers.get(&"payments");
+    let escalations = Escalations::default();
+    let _ = cb.add_listener(escalations.clone());
+    let watchdog = Watchdog::new(breakers).escalate(Duration::from_secs(60), |_| {});
+
+    cb.force_open(None);
+    assert!(watchdog.check().is_empty());
+    CLOCK.with(|clock| clock.advance(Duration::from_secs(60)));
+    assert_eq!(watchdog.check().len(), 1);
+    assert_eq!(*escalations.0.lock().unwrap(), vec![Duration::from_secs(60)]);
+}